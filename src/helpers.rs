@@ -0,0 +1,49 @@
+use rand::Rng;
+
+/// Generates a random vector of `i32`s.
+///
+/// # Params
+/// - `num_elements` - The number of elements to generate.
+///
+/// # Returns
+/// - The `Vec` of random `i32`s.
+pub fn generate_rand_vec(num_elements: u32) -> Vec<i32> {
+    let mut rng = rand::thread_rng();
+    let mut output = vec![];
+    for _ in 0..num_elements {
+        output.push(rng.gen_range(i32::MIN..=i32::MAX));
+    }
+
+    output
+}
+
+/// Checks to see if a list is sorted.
+///
+/// # Params
+/// - `list` - The list to check.
+/// - `in_order` - The closure that determines if its `first` argument comes before its `second` argument.
+///
+/// # Returns
+/// - `true` if every element is in order with the element after it, `false` otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::helpers::is_sorted;
+///
+/// assert!(is_sorted(&[1, 2, 2, 3], |first, second| -> bool { first <= second }));
+/// assert!(!is_sorted(&[1, 3, 2], |first, second| -> bool { first <= second }));
+///
+/// ```
+pub fn is_sorted<U, T>(list: &[T], in_order: U) -> bool
+where
+    U: Fn(&T, &T) -> bool,
+{
+    for i in 1..list.len() {
+        if !in_order(&list[i - 1], &list[i]) {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,5 @@
+pub mod helpers;
+pub mod sorts;
+
+#[cfg(test)]
+mod tests;
@@ -1,17 +1,22 @@
-mod sorts;
-mod tests;
-
 use std::time::Instant;
 
-use sorts::{
-    bubblesort::bubble_sort,
-    heapsort::heap_sort,
-    insertionsort::insertion_sort,
-    mergesort::{merge_sort_top_down, merge_sort_top_down_multithread},
-    quicksort::quick_sort,
-    selectionsort::selection_sort,
+use sorting_algorithms::{
+    helpers::{generate_rand_vec, is_sorted},
+    sorts::{
+        all_sorters,
+        bubblesort::bubble_sort,
+        heapsort::heap_sort,
+        insertionsort::insertion_sort,
+        mergesort::{
+            merge_sort_bottom_up, merge_sort_top_down, merge_sort_top_down_multithread_auto,
+        },
+        pool::SortPool,
+        quicksort::quick_sort,
+        radixsort::radix_sort,
+        selectionsort::selection_sort,
+        Sorter,
+    },
 };
-use tests::{generate_rand_vec, is_sorted};
 
 fn main() {
     println!("Hello, world!");
@@ -43,25 +48,27 @@ fn main() {
     list = merge_sort_top_down(&list, &|first, second| -> bool { first <= second });
     println!("Merge sort: {:?}", list);
 
-    let num_threads = 8; // This is the sweet spot on my machine that typically gets the best results
-    let list = generate_rand_vec(400000);
+    // Bottom up merge sort
+    let mut list: Vec<u32> = vec![0, 5, 6, 32, 78, 43];
+    merge_sort_bottom_up(&mut list, |first, second| -> bool { first <= second });
+    println!("Bottom up merge sort: {:?}", list);
+
+    let mut list = generate_rand_vec(400000);
     let closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
 
     let starting_time = Instant::now();
-    let merge_sort_result = merge_sort_top_down_multithread(
-        list,
-        // evil static closure
-        &|num1: &i32, num2: &i32| -> bool { num1 <= num2 },
-        num_threads,
-    );
+    // the number of threads is picked for whatever machine this runs on
+    let merge_sort_result = merge_sort_top_down_multithread_auto(&mut list, &closure);
 
     match merge_sort_result {
-        Ok(merged_list) => {
+        Ok(report) => {
             let elapsed_time = starting_time.elapsed();
-            assert_eq!(true, is_sorted(&merged_list, closure));
+            assert!(is_sorted(&list, closure));
             println!(
-                "Merge sort multithreaded took {} ms",
-                elapsed_time.as_millis()
+                "Merge sort multithreaded took {} ms ({} of {} available threads)",
+                elapsed_time.as_millis(),
+                report.num_threads,
+                report.available_parallelism
             );
         }
         Err(err_val) => {
@@ -74,23 +81,53 @@ fn main() {
     let closure = Box::new(|num1: &i32, num2: &i32| -> bool { num1 < num2 });
     list = quick_sort(list, &closure);
     // check that this is correct
-    assert_eq!(
-        true,
-        is_sorted(&list, |num1: &i32, num2: &i32| -> bool { num1 <= num2 })
-    );
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+        num1 <= num2
+    }));
     println!("Quicksort successful.");
 
+    // radix sort, which only works on numbers (or things with a number to sort them by)
+    let mut list = generate_rand_vec(400000);
+    radix_sort(&mut list);
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+        num1 <= num2
+    }));
+    println!("Radix sort successful.");
+
     // heapsort
     let mut list = vec![9, 2, 7, 8, 4, 5, 3, 1, 6, 10];
     let closure = Box::new(|num1: &i32, num2: &i32| -> bool { num1 < num2 });
     list = heap_sort(list, &closure);
     // check that this is correct
-    assert_eq!(
-        true,
-        is_sorted(
-            &vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
-            |num1: &i32, num2: &i32| -> bool { num1 <= num2 }
-        )
-    );
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+        num1 <= num2
+    }));
     println!("Heapsort successful: {:?}", list);
+
+    // every algorithm behind the same call site, with the pooled ones sharing their workers
+    let pool = SortPool::new(4).expect("Error when creating the sort pool");
+    let sorters: Vec<Box<dyn Sorter<i32> + '_>> = all_sorters(&pool);
+    let unsorted_list = generate_rand_vec(10000);
+    for sorter in sorters {
+        let mut list = unsorted_list.clone();
+        let capabilities = sorter.capabilities();
+
+        let starting_time = Instant::now();
+        sorter.sort_by(&mut list, &|num1, num2| num1.cmp(num2));
+        let elapsed_time = starting_time.elapsed();
+
+        assert!(
+            is_sorted(&list, |num1: &i32, num2: &i32| -> bool { num1 <= num2 }),
+            "{} left the list out of order",
+            sorter.name()
+        );
+        println!(
+            "{} took {} ms (stable: {}, in place: {}, parallel: {})",
+            sorter.name(),
+            elapsed_time.as_millis(),
+            capabilities.stable,
+            capabilities.in_place,
+            capabilities.parallel
+        );
+    }
 }
@@ -1,6 +1,335 @@
+use std::{cmp::Ordering, thread};
+
 pub mod bubblesort;
+pub mod error;
+pub mod heapsort;
 pub mod insertionsort;
+pub mod introsort;
 pub mod mergesort;
-pub mod selectionsort;
+pub mod pdqsort;
+pub mod pool;
 pub mod quicksort;
-pub mod heapsort;
\ No newline at end of file
+pub mod radixsort;
+pub mod selectionsort;
+pub mod timsort;
+pub mod validate;
+
+/// Describes the guarantees that a sorting algorithm makes, so that algorithms
+/// can be picked out of a table by what they offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Elements that compare as equal keep their original relative order.
+    pub stable: bool,
+    /// The sort works within the list itself instead of building copies of it.
+    pub in_place: bool,
+    /// The sort spreads its work across multiple threads.
+    pub parallel: bool,
+}
+
+/// A sorting algorithm that can be used behind a single call site.
+///
+/// Every algorithm in `sorts` has a unit struct implementing this trait, which makes it
+/// possible to swap between algorithms or register them in a table for benchmarking.
+///
+/// # Notes
+/// Every sort is panic safe: if the comparator panics partway through, the panic is passed
+/// on and the list is left holding each of its original elements exactly once, in an
+/// unspecified order. Nothing is leaked or dropped twice. The same goes for the free
+/// functions that sort a borrowed slice, and the parallel ones report the panic as a
+/// [`SortError::WorkerPanicked`](error::SortError::WorkerPanicked) instead.
+///
+/// A comparator that doesn't describe a consistent order (one that answers at random, for
+/// example) won't make a sort panic or loop forever either. The list just ends up in some
+/// unspecified order, still holding each of its elements exactly once. Use
+/// [`Checked`](validate::Checked) to find out if that is happening.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::{insertionsort::InsertionSort, quicksort::QuickSort, Sorter};
+///
+/// let sorters: Vec<Box<dyn Sorter<i32>>> = vec![Box::new(InsertionSort), Box::new(QuickSort)];
+/// for sorter in sorters {
+///     let mut list = vec![4, 5, 2, 1, 3];
+///     sorter.sort_by(&mut list, &|first, second| first.cmp(second));
+///     assert_eq!(vec![1, 2, 3, 4, 5], list);
+/// }
+///
+/// ```
+pub trait Sorter<T> {
+    /// The human readable name of the algorithm.
+    fn name(&self) -> &'static str;
+
+    /// The guarantees this algorithm makes.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Sorts the list in the order described by `compare`.
+    ///
+    /// # Params
+    /// - `list` - The slice to sort.
+    /// - `compare` - The closure that returns how its `first` argument is ordered
+    ///   relative to its `second` argument.
+    ///
+    /// # Notes
+    /// Sorts that can fail, like the parallel ones and [`Checked`](validate::Checked),
+    /// panic with the [`SortError`](error::SortError) here. Use [`Sorter::try_sort_by`] to
+    /// get the error back instead.
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync));
+
+    /// Sorts the list in the order described by `compare`, returning the error if the sort
+    /// fails instead of panicking.
+    ///
+    /// # Params
+    /// - `list` - The slice to sort.
+    /// - `compare` - The closure that returns how its `first` argument is ordered
+    ///   relative to its `second` argument.
+    ///
+    /// # Returns
+    /// - `Ok(())` once the list is sorted, or the error for a sort that can fail. Sorts that
+    ///   can't fail just call [`Sorter::sort_by`] and always return `Ok(())`.
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), error::SortError> {
+        self.sort_by(list, compare);
+
+        Ok(())
+    }
+
+    /// Sorts the list in ascending order.
+    ///
+    /// # Params
+    /// - `list` - The slice to sort.
+    fn sort(&self, list: &mut [T])
+    where
+        T: Ord,
+    {
+        self.sort_by(list, &|first, second| first.cmp(second));
+    }
+}
+
+/// Every sorting algorithm in `sorts` that can sort any type, behind the [`Sorter`] trait.
+///
+/// This is the one list to add a new algorithm to, so that everything that runs "every
+/// sort" (the benchmarks in `main` and the tests) picks it up. The radix sort isn't in it,
+/// as it only sorts numbers.
+///
+/// # Params
+/// - `pool` - The pool for the pooled sorts to run on. The parallel sorts that take a
+///   thread count use as many threads as the pool has.
+///
+/// # Returns
+/// - A [`Sorter`] for every algorithm.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::{all_sorters, pool::SortPool, Sorter};
+///
+/// let pool = SortPool::new(2).expect("Error when creating the sort pool");
+/// let sorters: Vec<Box<dyn Sorter<i32>>> = all_sorters(&pool);
+/// for sorter in sorters {
+///     let mut list = vec![4, 5, 2, 1, 3];
+///     sorter.sort(&mut list);
+///     assert_eq!(vec![1, 2, 3, 4, 5], list, "{} failed", sorter.name());
+/// }
+///
+/// ```
+pub fn all_sorters<T>(pool: &pool::SortPool) -> Vec<Box<dyn Sorter<T> + '_>>
+where
+    T: Clone + Send,
+{
+    let num_threads = pool.num_threads();
+    vec![
+        Box::new(bubblesort::BubbleSort),
+        Box::new(insertionsort::InsertionSort),
+        Box::new(selectionsort::SelectionSort),
+        Box::new(mergesort::MergeSort),
+        Box::new(mergesort::MergeSortBottomUp),
+        Box::new(timsort::TimSort),
+        Box::new(mergesort::MergeSortMultithread { num_threads }),
+        Box::new(mergesort::MergeSortPooled { pool }),
+        Box::new(quicksort::QuickSort),
+        Box::new(quicksort::QuickSortThreeWay),
+        Box::new(quicksort::DualPivotQuickSort),
+        Box::new(introsort::IntroSort),
+        Box::new(pdqsort::PdqSort),
+        Box::new(quicksort::QuickSortParallel { num_threads }),
+        Box::new(quicksort::QuickSortPooled { pool }),
+        Box::new(heapsort::HeapSort),
+    ]
+}
+
+/// Turns an `in_order` closure into an `Ordering`, no matter if the closure is strict
+/// (`<`, `>`) or not (`<=`, `>=`).
+///
+/// This is what lets the `in_order` versions of every sort accept either kind of
+/// closure. It asks the closure about both orders of the arguments, so every comparison
+/// made through it calls `in_order` twice.
+///
+/// # Params
+/// - `in_order` - The closure that determines if its `first` argument comes before its `second` argument.
+/// - `first` - The first element to compare.
+/// - `second` - The second element to compare.
+///
+/// # Returns
+/// - `Ordering::Less` if only `first` can come before `second`, `Ordering::Greater` if only
+///   `second` can come before `first`, and `Ordering::Equal` otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use std::cmp::Ordering;
+/// use sorting_algorithms::sorts::ordering_from_in_order;
+///
+/// let strict = |first: &i32, second: &i32| -> bool { first < second };
+/// let non_strict = |first: &i32, second: &i32| -> bool { first <= second };
+/// assert_eq!(Ordering::Equal, ordering_from_in_order(&strict, &1, &1));
+/// assert_eq!(Ordering::Equal, ordering_from_in_order(&non_strict, &1, &1));
+/// assert_eq!(Ordering::Less, ordering_from_in_order(&non_strict, &1, &2));
+///
+/// ```
+pub fn ordering_from_in_order<T, U>(in_order: &U, first: &T, second: &T) -> Ordering
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    match (in_order(first, second), in_order(second, first)) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        // either both orders are allowed (`<=`) or neither is (`<`), so they are equal
+        _ => Ordering::Equal,
+    }
+}
+
+/// The thread configuration that [`auto_thread_count`] picked for a parallel sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadCountReport {
+    /// The number of threads the sort runs with. Always at least `1`.
+    pub num_threads: u32,
+    /// The number of threads the machine can run at once, or `1` if it couldn't be found out.
+    pub available_parallelism: u32,
+    /// The length of the list being sorted.
+    pub list_len: usize,
+    /// Whether fewer threads were picked than are available because the list is too short
+    /// to keep them all busy.
+    pub limited_by_list_len: bool,
+}
+
+/// Picks how many threads a parallel sort should use for a list of a given length.
+///
+/// Uses as many threads as the machine can run at once, but no more than one for every
+/// `MIN_ELEMENTS_PER_THREAD` (4096) elements, so short lists don't pay for threads that
+/// have next to nothing to do. A list shorter than that gets a single thread.
+///
+/// # Params
+/// - `list_len` - The length of the list that is going to be sorted.
+///
+/// # Returns
+/// - The chosen configuration.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::auto_thread_count;
+///
+/// // not worth spawning anything for
+/// assert_eq!(1, auto_thread_count(100).num_threads);
+///
+/// let report = auto_thread_count(1_000_000);
+/// assert!(report.num_threads <= report.available_parallelism);
+///
+/// ```
+pub fn auto_thread_count(list_len: usize) -> ThreadCountReport {
+    // fewer elements than this aren't worth a thread of their own
+    static MIN_ELEMENTS_PER_THREAD: usize = 4096;
+
+    let available_parallelism = available_parallelism();
+    let max_useful_threads =
+        u32::try_from(usize::max(list_len / MIN_ELEMENTS_PER_THREAD, 1)).unwrap_or(u32::MAX);
+
+    ThreadCountReport {
+        num_threads: u32::min(available_parallelism, max_useful_threads),
+        available_parallelism,
+        list_len,
+        limited_by_list_len: max_useful_threads < available_parallelism,
+    }
+}
+
+/// The number of threads the machine can run at once, or `1` if it couldn't be found out.
+pub(crate) fn available_parallelism() -> u32 {
+    thread::available_parallelism()
+        .map(|num_threads| u32::try_from(num_threads.get()).unwrap_or(u32::MAX))
+        .unwrap_or(1)
+}
+
+/// Sorts a list of elements with an algorithm that is guaranteed to be stable, so that
+/// elements that compare as equal keep their original relative order.
+///
+/// Currently this is the bottom up merge sort, which doesn't need to clone the elements.
+/// Use this instead of naming an algorithm when the order of equal elements matters.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work, and the sort is stable with either.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::stable_sort;
+///
+/// // sort by the first value only, the second one shows the original order
+/// let mut list = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+/// stable_sort(&mut list, |first, second| first.0 <= second.0);
+/// assert_eq!(vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')], list);
+///
+/// ```
+pub fn stable_sort<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    stable_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Sorts a list of elements with a stable algorithm, using a closure that returns an `Ordering`.
+/// See [`stable_sort`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+pub fn stable_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    mergesort::merge_sort_bottom_up_by(list, compare);
+}
+
+/// Sorts a list of elements with a stable algorithm, ordering them by the key that the
+/// `key` closure extracts from each element. See [`stable_sort`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn stable_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    stable_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Sorts a list of elements with a stable algorithm, in ascending order. See [`stable_sort`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn stable_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    stable_sort_by(list, T::cmp);
+}
@@ -1,4 +1,6 @@
-use std::mem::swap;
+use std::{cmp::Ordering, mem::swap};
+
+use crate::sorts::{ordering_from_in_order, Capabilities, Sorter};
 
 /// Performs a bubble sort on a list of elements.
 /// Implemented using the non-naive approach (checks if the `Vec` is already sorted
@@ -6,11 +8,14 @@ use std::mem::swap;
 ///
 /// # Params
 /// - `list` - The `Vec` to sort.
-/// - `comparison_closure` - The closure to use to sort the array.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::bubblesort::bubble_sort;
 ///
 /// // create a list of elements
 /// let mut list = vec![4,5,2,1,3];
@@ -19,15 +24,44 @@ use std::mem::swap;
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn bubble_sort<T, U>(list: &mut Vec<T>, in_order: U)
+pub fn bubble_sort<T, U>(list: &mut [T], in_order: U)
 where
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    bubble_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a bubble sort on a list of elements, using a closure that returns an `Ordering`.
+/// Implemented using the non-naive approach (checks if the list is already sorted
+/// and breaks early if so). The sort is stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::bubblesort::bubble_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// bubble_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn bubble_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     for i in (1..list.len()).rev() {
         // assume it is sorted until we find something that is not in order
         let mut is_sorted = true;
         for j in 1..=i {
-            if !in_order(&list[j - 1], &list[j]) {
+            if compare(&list[j - 1], &list[j]) == Ordering::Greater {
                 // we found something out of order
                 is_sorted = false;
 
@@ -45,3 +79,50 @@ where
         }
     }
 }
+
+/// Performs a bubble sort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn bubble_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    bubble_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a bubble sort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn bubble_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    bubble_sort_by(list, T::cmp);
+}
+
+/// [`Sorter`] for [`bubble_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BubbleSort;
+
+impl<T> Sorter<T> for BubbleSort {
+    fn name(&self) -> &'static str {
+        "Bubble sort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        bubble_sort_by(list, compare);
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// The ways that a fallible sort can fail.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::{error::SortError, mergesort::merge_sort_top_down_multithread};
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// let result = merge_sort_top_down_multithread(&mut list, &|num1: &i32, num2: &i32| num1 <= num2, 0);
+/// assert_eq!(Err(SortError::InvalidThreadCount), result);
+///
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SortError {
+    /// A parallel sort or a [`SortPool`](crate::sorts::pool::SortPool) was asked to run
+    /// with no threads.
+    InvalidThreadCount,
+    /// A worker thread couldn't be started. Holds the error from the operating system.
+    ThreadSpawnFailed(String),
+    /// A worker thread panicked while sorting, most likely because the comparator did.
+    /// Holds the panic message, if it was a string.
+    WorkerPanicked(String),
+    /// The comparator doesn't describe a consistent order, for example because it says
+    /// that `a < b` and `b < a` at the same time. Holds a description of the elements that
+    /// showed it.
+    InconsistentComparator(String),
+}
+
+impl SortError {
+    /// Creates a [`SortError::WorkerPanicked`] from the payload of a caught panic.
+    ///
+    /// # Params
+    /// - `payload` - The payload that the panic was caught with.
+    pub(crate) fn from_panic(payload: &(dyn Any + Send)) -> SortError {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("unknown panic payload")
+        };
+
+        SortError::WorkerPanicked(message)
+    }
+
+    /// Runs a fallible sort's work on the calling thread, reporting a panic as a
+    /// [`SortError::WorkerPanicked`], the same way a panic on one of its worker threads would be.
+    ///
+    /// # Params
+    /// - `f` - The work to run.
+    ///
+    /// # Returns
+    /// - What `f` returned, or the error for the panic if it panicked.
+    pub(crate) fn catch_panic<R, F>(f: F) -> Result<R, SortError>
+    where
+        F: FnOnce() -> R,
+    {
+        panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|payload| SortError::from_panic(payload.as_ref()))
+    }
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortError::InvalidThreadCount => write!(f, "Cannot sort with no threads"),
+            SortError::ThreadSpawnFailed(message) => {
+                write!(f, "Error when spawning a thread to sort with: {}", message)
+            }
+            SortError::WorkerPanicked(message) => {
+                write!(f, "A thread panicked while sorting: {}", message)
+            }
+            SortError::InconsistentComparator(message) => {
+                write!(
+                    f,
+                    "The comparator does not describe a consistent order: {}",
+                    message
+                )
+            }
+        }
+    }
+}
+
+impl Error for SortError {}
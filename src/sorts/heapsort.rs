@@ -1,173 +1,546 @@
-/// Struct to represent a Heap with generics, as it will not always be possible to
-/// save the size as the first element in the vector, due to what may be stored in
-/// it (could be something that isn't a number).
-struct Heap<T>
-where
-    T: Clone,
-{
-    size: usize,
-    list: Vec<Option<T>>,
-}
+use std::cmp::Ordering;
+
+use crate::sorts::{ordering_from_in_order, Capabilities, Sorter};
 
 /// Performs a heapsort on a list of elements.
 ///
 /// # Params
 /// - `list` - The `Vec<T>` to sort.
 /// - `in_order` - The closure to use to sort the array. Determines if its `first`
-/// argument comes before its `second` argument.
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
 ///
 /// # Returns
 /// - The sorted `Vec<T>`.
 ///
 /// # Notes
-/// - Avoid using `<=` or `>=`, as this sort doesn't work properly with those comparisons.
-/// - Stick to using `<` and `>` for comparing elements.
+/// The `Vec` is moved into the sort, so its elements are dropped if `in_order` panics.
+/// Use [`heap_sort_in_place`] on a borrowed slice to keep them.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::heapsort::heap_sort;
 ///
 /// // sort using a closure to sort elements in ascending order
-/// // NOTE: avoid using <= or >=, as this sort doesn't work properly with those comparisons.
 /// let mut list = vec![4, 5, 2, 1, 3];
 /// list = heap_sort(list, &Box::new(|num1: &i32, num2: &i32| -> bool { num1 < num2 }));
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn heap_sort<T, U>(list: Vec<T>, in_order: &Box<U>) -> Vec<T>
+pub fn heap_sort<T, U>(mut list: Vec<T>, in_order: &U) -> Vec<T>
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    heap_sort_in_place(&mut list, in_order);
+
+    list
+}
+
+/// Performs a heapsort on a borrowed list of elements, sorting it in place.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Notes
+/// If `in_order` panics, the panic is passed on to the caller, but the slice still holds
+/// every one of its elements, in some order.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::heapsort::heap_sort_in_place;
+///
+/// // sort using a closure to sort elements in ascending order
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// heap_sort_in_place(&mut list, |num1: &i32, num2: &i32| -> bool { num1 < num2 });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn heap_sort_in_place<T, U>(list: &mut [T], in_order: U)
 where
-    T: Clone,              // we want to be able to clone the datatype held in the vector
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
 {
-    let mut the_heap = Heap::new(list);
-    the_heap.heapify(in_order);
+    heap_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a heapsort on a list of elements, using a closure that returns an `Ordering`.
+/// The list is sorted in place, using `O(1)` extra space. The sort is not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::heapsort::heap_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// heap_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn heap_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    heapify(list, &compare);
+
+    // the front of the list is the heap, the back is the sorted part
+    for heap_size in (1..list.len()).rev() {
+        delete_max_or_min(list, heap_size, &compare);
+    }
+}
+
+/// Performs a heapsort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn heap_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    heap_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a heapsort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn heap_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    heap_sort_by(list, T::cmp);
+}
+
+/// Heapifies the list, ordering it as a max heap (or a min heap when sorting in
+/// descending order) rooted at index `0`.
+///
+/// # Params
+/// - `list` - The slice to heapify.
+/// - `compare` - The closure to sort the heap with.
+fn heapify<T, F>(list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // percolate down from every parent, starting with the last one
+    for parent_idx in (0..list.len() / 2).rev() {
+        percolate_down(list, parent_idx, list.len(), compare);
+    }
+}
+
+/// Performs a percolate down on the heap from the parent index.
+///
+/// The children of the element at `idx` are at `2 * idx + 1` and `2 * idx + 2`.
+///
+/// # Params
+/// - `list` - The slice holding the heap.
+/// - `parent_idx` - The parent index to percolate down from.
+/// - `heap_size` - The number of elements at the front of `list` that are part of the heap.
+/// - `compare` - The closure to sort the heap with.
+fn percolate_down<T, F>(list: &mut [T], mut parent_idx: usize, heap_size: usize, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    while parent_idx * 2 + 1 < heap_size {
+        // while the parent index has at least one child
+
+        // find the larger of the two children (if the second child exists)
+        let mut max_child_idx = parent_idx * 2 + 1;
+        if max_child_idx + 1 < heap_size
+            && compare(&list[max_child_idx], &list[max_child_idx + 1]) == Ordering::Less
+        {
+            // if the second child exists and is larger than the first child
+            // then the second child is now the max
+            max_child_idx += 1;
+        }
+
+        if compare(&list[parent_idx], &list[max_child_idx]) != Ordering::Less {
+            // the parent is already in place
+            break;
+        }
 
-    for _ in (1..=the_heap.size).rev() {
-        the_heap.delete_max_or_min(in_order);
+        list.swap(parent_idx, max_child_idx);
+
+        // now set the parent_idx to be the index where we just swapped this parent to
+        parent_idx = max_child_idx;
     }
+}
+
+/// Deletes the max/min element in the heap, moving it to just after the end of the heap
+/// and shrinking the heap by one element.
+///
+/// # Params
+/// - `list` - The slice holding the heap.
+/// - `heap_size` - The number of elements in the heap, after the max/min is removed.
+/// - `compare` - The closure to sort the heap with.
+fn delete_max_or_min<T, F>(list: &mut [T], heap_size: usize, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // swap first and last element in the heap
+    list.swap(0, heap_size);
+
+    percolate_down(list, 0, heap_size, compare);
+}
 
-    the_heap.obtain_sorted_list()
+/// A handle to an element pushed onto a [`Heap`], used to change its value later on.
+///
+/// Handles stay valid until their element is popped off the heap. After that, every
+/// method that takes the handle treats it as not being in the heap anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeapHandle {
+    slot: usize,
+    generation: usize,
+}
+
+/// Keeps track of where the element for a [`HeapHandle`] currently is in the heap.
+#[derive(Debug)]
+struct Slot {
+    position: Option<usize>,
+    generation: usize,
+}
+
+/// A binary heap priority queue, ordered by a comparator closure.
+///
+/// The element that comes first according to the comparator is always at the top of the
+/// heap, so the same closures that sort a list in ascending order pop its elements from
+/// smallest to largest.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::heapsort::Heap;
+///
+/// // pop the tasks with the highest priority first
+/// let mut tasks = Heap::with_comparator(|first: &(u32, &str), second: &(u32, &str)| {
+///     second.0.cmp(&first.0)
+/// });
+/// tasks.push((1, "clean up"));
+/// let handle = tasks.push((2, "compile"));
+/// tasks.push((5, "deploy"));
+///
+/// // bump the priority of an element that is already in the heap
+/// assert_eq!(Ok((2, "compile")), tasks.decrease_key(handle, (9, "compile")));
+///
+/// assert_eq!(Some((9, "compile")), tasks.pop());
+/// assert_eq!(Some(&(5, "deploy")), tasks.peek());
+/// assert_eq!(vec![(5, "deploy"), (1, "clean up")], tasks.into_sorted_vec());
+///
+/// ```
+pub struct Heap<T, C = fn(&T, &T) -> Ordering> {
+    /// The elements in heap order, each with the slot that its handle points to.
+    entries: Vec<(T, usize)>,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+    compare: C,
 }
 
 impl<T> Heap<T>
 where
-    T: Clone,
+    T: Ord,
+{
+    /// Creates a new, empty `Heap` that pops elements in ascending order.
+    pub fn new() -> Self {
+        Self::with_comparator(T::cmp)
+    }
+}
+
+impl<T> Default for Heap<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for Heap<T>
+where
+    T: Ord,
 {
-    /// Creates a new `Heap` from a list of `T`, inserting a `None`
-    /// at the beginning to make math easier.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_iter_by(iter, T::cmp)
+    }
+}
+
+impl<T, C> Heap<T, C>
+where
+    C: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    /// Creates a new, empty `Heap` ordered by a comparator closure.
     ///
     /// # Params
-    /// - `list` - The `Vec` of `T` to create a `Heap` from.
-    pub fn new(list: Vec<T>) -> Self {
-        let list_size = list.len();
-        // new list starting with `None` to make math easier when performing operations on the vector
-        let mut new_list = vec![None];
-        for element in list {
-            new_list.push(Some(element));
-        }
-
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument. The element that comes first is popped first.
+    pub fn with_comparator(compare: C) -> Self {
         Self {
-            size: list_size,
-            list: new_list,
+            entries: vec![],
+            slots: vec![],
+            free_slots: vec![],
+            compare,
         }
     }
 
-    /// Heapifies the `Heap` list, ordering the heap as a min/max heap.
+    /// Creates a new `Heap` out of every element of an iterator, ordered by a comparator
+    /// closure. The elements are heapified all at once, which takes `O(n)` time.
     ///
     /// # Params
-    /// - `in_order` - The boxed closure to sort the heap with.
-    pub fn heapify<U>(&mut self, in_order: &Box<U>)
+    /// - `iter` - The elements to put in the heap.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument. The element that comes first is popped first.
+    pub fn from_iter_by<I>(iter: I, compare: C) -> Self
     where
-        U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+        I: IntoIterator<Item = T>,
     {
-        let mut i = self.size / 2;
-        while i > 0 {
-            // percolate down from this parent
-            self.percolate_down(i, in_order);
-            i -= 1;
+        let mut the_heap = Self::with_comparator(compare);
+        for value in iter {
+            let slot = the_heap.allocate_slot(the_heap.entries.len());
+            the_heap.entries.push((value, slot.slot));
         }
+        the_heap.heapify();
+
+        the_heap
     }
 
-    /// Performs a percolate down on the heap from the parent index.
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no elements in the heap.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Pushes an element onto the heap, percolating it up to its place.
     ///
     /// # Params
-    /// - `parent_idx` - The parent index to percolate down from.
-    /// - `in_order` - The boxed closure to sort the heap with.
-    fn percolate_down<U>(&mut self, mut parent_idx: usize, in_order: &Box<U>)
-    where
-        U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
-    {
-        while parent_idx * 2 <= self.size {
-            // while the parent index has at least one child
-
-            // find the larger of the two children (if the second child exists)
-            let mut max_child_idx = parent_idx * 2;
-
-            if max_child_idx + 1 <= self.size {
-                if let (Some(first), Some(second)) =
-                    (&self.list[max_child_idx], &self.list[max_child_idx + 1])
-                {
-                    if in_order(first, second) {
-                        // if the second child exists and is larger than the first child
-                        // then the second child is now the max
-                        max_child_idx += 1;
-                    }
-                } else {
-                    // NOTE: check if this code path is even possible
-                    panic!("Could not get value when percolating down");
-                }
-            }
-            if let (Some(parent), Some(child)) = (&self.list[parent_idx], &self.list[max_child_idx])
-            {
-                if in_order(parent, child) {
-                    // swap elements
-                    let temp = self.list[parent_idx].clone();
-                    self.list[parent_idx] = self.list[max_child_idx].clone();
-                    self.list[max_child_idx] = temp;
-                }
-            }
+    /// - `value` - The element to push.
+    ///
+    /// # Returns
+    /// - The handle to use with [`Heap::decrease_key`] for this element.
+    pub fn push(&mut self, value: T) -> HeapHandle {
+        let idx = self.entries.len();
+        let handle = self.allocate_slot(idx);
+        self.entries.push((value, handle.slot));
+        self.percolate_up(idx);
+
+        handle
+    }
+
+    /// Returns the element at the top of the heap without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.entries.first().map(|(value, _)| value)
+    }
 
-            // now set the parent_idx to be the index where we just swapped this parent to
-            parent_idx = max_child_idx;
+    /// Removes the element at the top of the heap.
+    ///
+    /// # Returns
+    /// - `Some(T)` with the element that comes first, or `None` if the heap is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.entries.is_empty() {
+            return None;
         }
+
+        // swap first and last element in the heap, then take off the last one
+        let last_idx = self.entries.len() - 1;
+        self.swap_entries(0, last_idx);
+        let (value, slot) = self.entries.pop()?;
+        self.free_slot(slot);
+
+        self.percolate_down(0);
+
+        Some(value)
     }
 
-    /// Deletes the max/min element in the heap, adding it at the end of the heap
-    /// and decreasing the size of the heap.
+    /// Returns `true` if the element for a handle is still in the heap.
     ///
     /// # Params
-    /// - `in_order` - The boxed closure to sort the heap with.
-    fn delete_max_or_min<U>(&mut self, in_order: &Box<U>)
-    where
-        U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
-    {
-        // swap first and last element in the heap
-        let temp = self.list[1].clone();
-        self.list[1] = self.list[self.size].clone();
-        self.list[self.size] = temp;
+    /// - `handle` - The handle returned when the element was pushed.
+    pub fn contains(&self, handle: HeapHandle) -> bool {
+        self.position_of(handle).is_some()
+    }
+
+    /// Returns the element for a handle, if it is still in the heap.
+    ///
+    /// # Params
+    /// - `handle` - The handle returned when the element was pushed.
+    pub fn get(&self, handle: HeapHandle) -> Option<&T> {
+        self.position_of(handle).map(|idx| &self.entries[idx].0)
+    }
+
+    /// Replaces the element for a handle with a new value, moving it to its new place in
+    /// the heap.
+    ///
+    /// The new value is normally one that comes before the old value, moving the element
+    /// towards the top of the heap (like lowering a distance in Dijkstra's algorithm). A
+    /// value that comes after the old one is also handled, by moving the element down.
+    ///
+    /// # Params
+    /// - `handle` - The handle returned when the element was pushed.
+    /// - `value` - The new value for the element.
+    ///
+    /// # Returns
+    /// - `Ok(T)` with the old value, or `Err(T)` giving back `value` if the element is no
+    ///   longer in the heap.
+    pub fn decrease_key(&mut self, handle: HeapHandle, value: T) -> Result<T, T> {
+        let idx = match self.position_of(handle) {
+            Some(idx) => idx,
+            None => return Err(value),
+        };
+
+        let old_value = std::mem::replace(&mut self.entries[idx].0, value);
+        let idx = self.percolate_up(idx);
+        self.percolate_down(idx);
+
+        Ok(old_value)
+    }
 
-        self.size -= 1;
+    /// Consumes the heap, returning its elements in the order they would have been popped.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut list: Vec<T> = self.entries.into_iter().map(|(value, _)| value).collect();
+        heap_sort_by(&mut list, &self.compare);
 
-        self.percolate_down(1, in_order);
+        list
     }
 
-    /// Obtains a sorted list from the `Heap` data, converting the
-    /// `Vec<Option<T>>` back to a `Vec<T>`.
+    /// Checks if the element at `first_idx` comes before the element at `second_idx`.
+    fn comes_before(&self, first_idx: usize, second_idx: usize) -> bool {
+        (self.compare)(&self.entries[first_idx].0, &self.entries[second_idx].0) == Ordering::Less
+    }
+
+    /// Heapifies the entries, so that every parent comes before its children.
+    fn heapify(&mut self) {
+        for parent_idx in (0..self.entries.len() / 2).rev() {
+            self.percolate_down(parent_idx);
+        }
+    }
+
+    /// Performs a percolate up on the heap from the child index.
+    ///
+    /// # Params
+    /// - `child_idx` - The child index to percolate up from.
     ///
     /// # Returns
-    /// - The sorted list.
-    fn obtain_sorted_list(self) -> Vec<T> {
-        let mut output_list = vec![];
-
-        for element in self.list {
-            match element {
-                Some(value) => {
-                    output_list.push(value);
+    /// - The index that the element ended up at.
+    fn percolate_up(&mut self, mut child_idx: usize) -> usize {
+        while child_idx > 0 {
+            let parent_idx = (child_idx - 1) / 2;
+            if !self.comes_before(child_idx, parent_idx) {
+                // the child is already in place
+                break;
+            }
+
+            self.swap_entries(parent_idx, child_idx);
+            child_idx = parent_idx;
+        }
+
+        child_idx
+    }
+
+    /// Performs a percolate down on the heap from the parent index.
+    ///
+    /// # Params
+    /// - `parent_idx` - The parent index to percolate down from.
+    fn percolate_down(&mut self, mut parent_idx: usize) {
+        let size = self.entries.len();
+        while parent_idx * 2 + 1 < size {
+            // find the child that comes first (if the second child exists)
+            let mut first_child_idx = parent_idx * 2 + 1;
+            if first_child_idx + 1 < size && self.comes_before(first_child_idx + 1, first_child_idx)
+            {
+                first_child_idx += 1;
+            }
+
+            if !self.comes_before(first_child_idx, parent_idx) {
+                // the parent is already in place
+                break;
+            }
+
+            self.swap_entries(parent_idx, first_child_idx);
+            parent_idx = first_child_idx;
+        }
+    }
+
+    /// Swaps two entries, keeping the slots of their handles up to date.
+    fn swap_entries(&mut self, first_idx: usize, second_idx: usize) {
+        self.entries.swap(first_idx, second_idx);
+        self.slots[self.entries[first_idx].1].position = Some(first_idx);
+        self.slots[self.entries[second_idx].1].position = Some(second_idx);
+    }
+
+    /// Finds where the element for a handle is in the heap.
+    fn position_of(&self, handle: HeapHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot)?;
+        if slot.generation == handle.generation {
+            slot.position
+        } else {
+            None
+        }
+    }
+
+    /// Gets a slot for a new element, reusing the slot of a popped element if possible.
+    fn allocate_slot(&mut self, position: usize) -> HeapHandle {
+        match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot].position = Some(position);
+                HeapHandle {
+                    slot,
+                    generation: self.slots[slot].generation,
                 }
-                None => { /* do nothing */ }
             }
+            None => {
+                self.slots.push(Slot {
+                    position: Some(position),
+                    generation: 0,
+                });
+                HeapHandle {
+                    slot: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Frees the slot of a popped element, so that its old handles stop working.
+    fn free_slot(&mut self, slot: usize) {
+        self.slots[slot].position = None;
+        self.slots[slot].generation += 1;
+        self.free_slots.push(slot);
+    }
+}
+
+/// [`Sorter`] for [`heap_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeapSort;
+
+impl<T> Sorter<T> for HeapSort {
+    fn name(&self) -> &'static str {
+        "Heapsort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
         }
+    }
 
-        output_list
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        heap_sort_by(list, compare);
     }
 }
@@ -1,14 +1,19 @@
-use std::mem::swap;
+use std::{cmp::Ordering, mem::swap};
+
+use crate::sorts::{ordering_from_in_order, Capabilities, Sorter};
 
 /// Performs an insertion sort on a list of elements.
 ///
 /// # Params
 /// - `list` - The `Vec` to sort.
-/// - `comparison_closure` - The closure to use to sort the array.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::insertionsort::insertion_sort;
 ///
 /// // create a list of elements
 /// let mut list = vec![4,5,2,1,3];
@@ -17,13 +22,41 @@ use std::mem::swap;
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn insertion_sort<T, U>(list: &mut Vec<T>, in_order: U)
+pub fn insertion_sort<T, U>(list: &mut [T], in_order: U)
 where
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    insertion_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs an insertion sort on a list of elements, using a closure that returns an `Ordering`.
+/// The sort is stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::insertionsort::insertion_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// insertion_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn insertion_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     for i in 1..list.len() {
         for j in (1..=i).rev() {
-            if !in_order(&list[j - 1], &list[j]) {
+            if compare(&list[j - 1], &list[j]) == Ordering::Greater {
                 // split the vector to be able to swap behind a mutable reference
                 let (first, second) = list.split_at_mut(j);
 
@@ -35,3 +68,110 @@ where
         }
     }
 }
+
+/// Performs a binary insertion sort on a list of elements, using a closure that returns an
+/// `Ordering`. The sort is stable.
+///
+/// Each element finds its place in the sorted part of the list with a binary search instead
+/// of comparing its way back one element at a time, so it makes `O(n log n)` comparisons.
+/// It still moves `O(n^2)` elements, which makes it a good fit for short lists with
+/// comparisons that are expensive.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::insertionsort::binary_insertion_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// binary_insertion_sort_by(&mut list, |first, second| first.cmp(second));
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn binary_insertion_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    binary_insertion_sort_from(list, 1, &compare);
+}
+
+/// Performs a binary insertion sort on a list whose first `sorted_len` elements are already
+/// sorted, inserting the rest of the elements one at a time.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `sorted_len` - The length of the sorted run at the start of the list.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+pub(crate) fn binary_insertion_sort_from<T, F>(list: &mut [T], sorted_len: usize, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    for i in usize::max(sorted_len, 1)..list.len() {
+        // find the first element that comes after the new one, so that equal elements
+        // keep their order
+        let (mut low, mut high) = (0, i);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if compare(&list[i], &list[mid]) == Ordering::Less {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        // shift everything after that place along by one, and put the new element there
+        list[low..=i].rotate_right(1);
+    }
+}
+
+/// Performs an insertion sort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn insertion_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    insertion_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs an insertion sort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn insertion_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    insertion_sort_by(list, T::cmp);
+}
+
+/// [`Sorter`] for [`insertion_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InsertionSort;
+
+impl<T> Sorter<T> for InsertionSort {
+    fn name(&self) -> &'static str {
+        "Insertion sort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        insertion_sort_by(list, compare);
+    }
+}
@@ -0,0 +1,161 @@
+use std::cmp::Ordering;
+
+use crate::sorts::{
+    heapsort::heap_sort_by,
+    insertionsort::insertion_sort_by,
+    ordering_from_in_order,
+    quicksort::{partition, CUTOFF},
+    Capabilities, Sorter,
+};
+
+/// Performs an introsort on a list of elements.
+///
+/// An introsort is a quicksort that keeps track of how deep it has gone. Once a partition
+/// has been split more than `2 * log2(n)` times, the pivots are clearly going badly, so
+/// that partition is finished off with a heapsort instead. This keeps the worst case at
+/// `O(n log n)` even on inputs built to defeat the median of three, while running as fast
+/// as the quicksort on everything else. Short partitions are insertion sorted, like in
+/// the quicksort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::introsort::intro_sort;
+///
+/// // create a list of elements
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort using a closure to sort elements in ascending order
+/// intro_sort(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn intro_sort<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    intro_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs an introsort on a list of elements, using a closure that returns an `Ordering`.
+/// The list is sorted in place, without any allocations or clones. The sort is not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::introsort::intro_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// intro_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn intro_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let depth_limit = 2 * list.len().max(1).ilog2();
+    intro_sort_recursive(list, &compare, depth_limit);
+}
+
+/// Performs an introsort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn intro_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    intro_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs an introsort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn intro_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    intro_sort_by(list, T::cmp);
+}
+
+/// Performs a recursive introsort in place. Helper function for intro_sort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `depth_limit` - How many more times the list can be partitioned before switching
+///   to a heapsort.
+///
+/// # Notes
+/// - Like the quicksort, only the smaller partition is recursed into, while the larger
+///   one is handled by the loop. Both of them count against the depth limit.
+fn intro_sort_recursive<T, F>(mut list: &mut [T], compare: &F, mut depth_limit: u32)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // keep going while we are above the cutoff
+    while list.len() >= CUTOFF {
+        if depth_limit == 0 {
+            // the pivots have been bad for too long, so stop partitioning
+            heap_sort_by(list, compare);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot_idx = partition(list, compare);
+
+        // the pivot is already in place, so it isn't part of either partition
+        let (left_list, right_list) = list.split_at_mut(pivot_idx);
+        let right_list = &mut right_list[1..];
+        if left_list.len() < right_list.len() {
+            intro_sort_recursive(left_list, compare, depth_limit);
+            list = right_list;
+        } else {
+            intro_sort_recursive(right_list, compare, depth_limit);
+            list = left_list;
+        }
+    }
+
+    insertion_sort_by(list, compare);
+}
+
+/// [`Sorter`] for [`intro_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IntroSort;
+
+impl<T> Sorter<T> for IntroSort {
+    fn name(&self) -> &'static str {
+        "Introsort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        intro_sort_by(list, compare);
+    }
+}
@@ -1,28 +1,113 @@
-use std::thread;
+use std::{cmp::Ordering, ptr, rc::Rc};
+
+use crate::sorts::{
+    auto_thread_count,
+    error::SortError,
+    heapsort::Heap,
+    insertionsort::insertion_sort_by,
+    ordering_from_in_order,
+    pool::{default_pool, SortPool},
+    Capabilities, Sorter, ThreadCountReport,
+};
 
 /// Performs a merge sort on a list of elements.
 ///
 /// # Params
 /// - `list` - The `Vec<T>` to sort.
 /// - `in_order` - The closure to use to sort the array. Determines if its `first`
-/// argument comes before its `second` argument.
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work, and the sort is stable with either.
 ///
 /// # Returns
 /// - The sorted `Vec<T>`.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_top_down;
 ///
 /// // sort using a closure to sort elements in ascending order
-/// let list = insertion_sort(&vec![4, 5, 2, 1, 3], &|first, second| -> bool { first <= second });
+/// let list = merge_sort_top_down(&vec![4, 5, 2, 1, 3], &|first, second| -> bool { first <= second });
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn merge_sort_top_down<T, U>(list: &Vec<T>, in_order: &U) -> Vec<T>
+pub fn merge_sort_top_down<T, U>(list: &[T], in_order: &U) -> Vec<T>
 where
     T: Clone,              // we want to be able to clone the datatype held in the vector
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    merge_sort_top_down_recursive(list, &|first: &T, second: &T| {
+        ordering_from_in_order(in_order, first, second)
+    })
+}
+
+/// Performs a merge sort on a list of elements, using a closure that returns an `Ordering`.
+/// The sort is stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_top_down_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// merge_sort_top_down_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn merge_sort_top_down_by<T, F>(list: &mut [T], compare: F)
+where
+    T: Clone,                  // we want to be able to clone the datatype held in the vector
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let sorted = merge_sort_top_down_recursive(list, &compare);
+    list.clone_from_slice(&sorted);
+}
+
+/// Performs a merge sort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn merge_sort_top_down_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    T: Clone,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    merge_sort_top_down_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a merge sort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn merge_sort_top_down_ord<T>(list: &mut [T])
+where
+    T: Clone + Ord,
+{
+    merge_sort_top_down_by(list, T::cmp);
+}
+
+/// Performs a recursive merge sort. Helper function for the top down merge sort entry points.
+///
+/// # Params
+/// - `list` - The list to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The sorted `Vec<T>`.
+fn merge_sort_top_down_recursive<T, F>(list: &[T], compare: &F) -> Vec<T>
+where
+    T: Clone,                  // we want to be able to clone the datatype held in the vector
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     if list.len() <= 1 {
         // we have a sorted list
@@ -30,10 +115,221 @@ where
     }
     let midpoint = list.len() / 2;
     let (first, second) = list.split_at(midpoint);
-    let left = merge_sort_top_down(&first.to_vec(), in_order);
-    let right = merge_sort_top_down(&second.to_vec(), in_order);
+    let left = merge_sort_top_down_recursive(first, compare);
+    let right = merge_sort_top_down_recursive(second, compare);
+
+    merge_vecs(&left, &right, compare)
+}
+
+/// Performs a bottom up (iterative) merge sort on a list of elements.
+///
+/// # Params
+/// - `list` - The `Vec` to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work, and the sort is stable with either.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_bottom_up;
+///
+/// // create a list of elements
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort using a closure to sort elements in ascending order
+/// merge_sort_bottom_up(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn merge_sort_bottom_up<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    merge_sort_bottom_up_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a bottom up (iterative) merge sort on a list of elements, using a closure
+/// that returns an `Ordering`. The sort is stable.
+///
+/// Short runs are sorted with an insertion sort first, then neighbouring runs are merged
+/// back into `list` with runs of doubling width. A single scratch buffer of `n / 2`
+/// elements is allocated up front and reused by every merge, and elements are moved
+/// rather than cloned.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_bottom_up_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// merge_sort_bottom_up_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn merge_sort_bottom_up_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    static RUN_LEN: usize = 16;
+
+    let len = list.len();
+    if len <= 1 {
+        // we have a sorted list
+        return;
+    }
+
+    // sort the short runs that the merging starts from
+    for run in list.chunks_mut(RUN_LEN) {
+        insertion_sort_by(run, &compare);
+    }
+
+    // the shorter run of a merge is the one copied out, and it is never longer than half the list
+    let mut buffer = Vec::with_capacity(len / 2);
+    let mut width = RUN_LEN;
+    while width < len {
+        let mut start = 0;
+        while start + width < len {
+            let end = usize::min(start + 2 * width, len);
+            merge_with_buffer(&mut list[start..end], width, &mut buffer, &compare);
+            start = end;
+        }
+
+        width *= 2;
+    }
+}
+
+/// Performs a bottom up merge sort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn merge_sort_bottom_up_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    merge_sort_bottom_up_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a bottom up merge sort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn merge_sort_bottom_up_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    merge_sort_bottom_up_by(list, T::cmp);
+}
+
+/// The part of a merge that has been copied out to the scratch buffer, along with the
+/// gap in the list that it belongs in.
+///
+/// If the comparator panics partway through a merge, dropping this copies whatever is
+/// left in the buffer back into the gap, so the list still holds every element exactly once.
+pub(crate) struct MergeHole<T> {
+    pub(crate) start: *mut T,
+    pub(crate) end: *mut T,
+    pub(crate) dest: *mut T,
+}
 
-    merge_vecs(&left, &right, in_order)
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        // SAFETY: `start..end` are the elements still in the buffer, and `dest` is the start
+        // of a gap in the list of exactly that many elements.
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+/// Merges the two sorted runs `list[..mid]` and `list[mid..]` in place, so that all of
+/// `list` is sorted. The merge is stable.
+///
+/// The shorter of the two runs is moved into `buffer`, then merged back into the list
+/// from the front (when the left run is shorter) or from the back (when the right run is
+/// shorter). Nothing is cloned, and `buffer` never takes ownership of the elements.
+///
+/// # Params
+/// - `list` - The slice holding both runs.
+/// - `mid` - The index that the right run starts at.
+/// - `buffer` - The scratch buffer. Must have a capacity of at least the shorter run's length.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+pub(crate) fn merge_with_buffer<T, F>(list: &mut [T], mid: usize, buffer: &mut Vec<T>, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let len = list.len();
+    if mid == 0 || mid >= len || compare(&list[mid - 1], &list[mid]) != Ordering::Greater {
+        // the runs are already in order
+        return;
+    }
+    assert!(buffer.capacity() >= usize::min(mid, len - mid));
+
+    let list_ptr = list.as_mut_ptr();
+    let buffer_ptr = buffer.as_mut_ptr();
+
+    // SAFETY: the shorter run is moved into the buffer, which leaves a gap of the same size in
+    // the list. Every step of the merge moves one element into the gap and moves the gap along,
+    // so no element is ever in two places, and `MergeHole` fills the gap in again at the end
+    // (or if `compare` panics).
+    unsafe {
+        if mid <= len - mid {
+            // the left run is shorter, so merge forwards
+            ptr::copy_nonoverlapping(list_ptr, buffer_ptr, mid);
+            let mut hole = MergeHole {
+                start: buffer_ptr,
+                end: buffer_ptr.add(mid),
+                dest: list_ptr,
+            };
+
+            let mut right = list_ptr.add(mid);
+            let right_end = list_ptr.add(len);
+            while hole.start < hole.end && right < right_end {
+                // only take from the right run if it comes strictly first, which keeps this stable
+                if compare(&*right, &*hole.start) == Ordering::Less {
+                    ptr::copy_nonoverlapping(right, hole.dest, 1);
+                    right = right.add(1);
+                } else {
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                    hole.start = hole.start.add(1);
+                }
+                hole.dest = hole.dest.add(1);
+            }
+        } else {
+            // the right run is shorter, so merge backwards
+            ptr::copy_nonoverlapping(list_ptr.add(mid), buffer_ptr, len - mid);
+            let mut hole = MergeHole {
+                start: buffer_ptr,
+                end: buffer_ptr.add(len - mid),
+                dest: list_ptr.add(mid),
+            };
+
+            let mut out = list_ptr.add(len);
+            while list_ptr < hole.dest && hole.start < hole.end {
+                out = out.sub(1);
+                // only take from the left run if it comes strictly last, which keeps this stable
+                if compare(&*hole.end.sub(1), &*hole.dest.sub(1)) == Ordering::Less {
+                    hole.dest = hole.dest.sub(1);
+                    ptr::copy_nonoverlapping(hole.dest, out, 1);
+                } else {
+                    hole.end = hole.end.sub(1);
+                    ptr::copy_nonoverlapping(hole.end, out, 1);
+                }
+            }
+        }
+    }
 }
 
 /// Merges the elements from two different vectors into one vector, in the
@@ -42,21 +338,22 @@ where
 /// # Params
 /// - `left` - The left `Vec` to merge.
 /// - `right` - The right `Vec` to merge.
-/// - `in_order` - The closure that determines if its `first` argument comes before its `second` argument.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
 ///
 /// # Returns
 /// - The merged `Vec`.
-fn merge_vecs<T, U>(left: &Vec<T>, right: &Vec<T>, in_order: &U) -> Vec<T>
+fn merge_vecs<T, F>(left: &[T], right: &[T], compare: &F) -> Vec<T>
 where
     T: Clone,
-    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     let mut merged = vec![];
 
     // add all the elements to the resulting vec
     let mut indicies = vec![(0, left), (0, right)];
     loop {
-        let element_to_add = find_next_element(&mut indicies, in_order);
+        let element_to_add = find_next_element(&mut indicies, compare);
 
         match element_to_add {
             None => {
@@ -72,73 +369,216 @@ where
     merged
 }
 
-/// Merges the elements from one or more different vectors into one vector, in the
-/// proper sorting order.
+/// Merges any number of sorted lists into one sorted `Vec`.
+///
+/// This collects a [`MergeIter`], which keeps the first element of every list in a [`Heap`],
+/// so each element of the output takes `O(log k)` comparisons to find for `k` lists, instead
+/// of a scan over every list.
 ///
 /// # Params
-/// - `left` - The left `Vec` to merge.
-/// - `right` - The right `Vec` to merge.
-/// - `in_order` - The closure that determines if its `first` argument comes before its `second` argument.
+/// - `lists` - The lists to merge. Each one can be anything that turns into an iterator
+///   (a `Vec`, a slice iterator, a range, ...), and must already be sorted by `compare`.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
 ///
 /// # Returns
 /// - The merged `Vec`.
-#[allow(dead_code)]
-fn merge_multiple<T, U>(lists: &Vec<Vec<T>>, in_order: &U) -> Vec<T>
+///
+/// # Notes
+/// - The merge is stable: elements that are equal keep the order of the lists they came
+///   from, and the order they had within their list.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_k_sorted;
+///
+/// let shards = vec![vec![1, 4, 7], vec![2, 5, 8], vec![3, 6, 9]];
+/// let merged = merge_k_sorted(shards, |first, second| first.cmp(second));
+/// assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], merged);
+///
+/// // iterators work too
+/// let merged = merge_k_sorted([0..3, 1..2], |first, second| first.cmp(second));
+/// assert_eq!(vec![0, 1, 1, 2], merged);
+///
+/// ```
+pub fn merge_k_sorted<I, T, F>(lists: I, compare: F) -> Vec<T>
 where
-    T: Clone,
-    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+    I: IntoIterator,
+    I::Item: IntoIterator<Item = T>,
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
-    let mut merged = vec![];
+    MergeIter::new(lists, compare).collect()
+}
 
-    let mut indicies = vec![];
-    // add all the elements to the resulting vec
-    for inner_list in lists {
-        indicies.push((0, inner_list));
+/// A lazy merge of any number of iterators that are already sorted by the same comparator.
+///
+/// Only the next element of every input is held at a time, so sorted streams can be merged
+/// without collecting them first. Elements come out in sorted order, with equal elements
+/// taken from the earlier input first (so the merge is stable).
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::MergeIter;
+///
+/// let first_log = vec![(1, "boot"), (4, "login"), (9, "logout")];
+/// let second_log = vec![(2, "cron"), (4, "login"), (5, "backup")];
+/// let mut merged = MergeIter::new([first_log, second_log], |first, second| first.cmp(second));
+/// assert_eq!(Some((1, "boot")), merged.next());
+/// assert_eq!(Some((2, "cron")), merged.next());
+///
+/// // duplicates can be dropped along the way
+/// let merged: Vec<i32> = MergeIter::new([1..4, 2..6], |first, second| first.cmp(second))
+///     .dedup()
+///     .collect();
+/// assert_eq!(vec![1, 2, 3, 4, 5], merged);
+///
+/// ```
+pub struct MergeIter<'a, I>
+where
+    I: Iterator,
+{
+    sources: Vec<I>,
+    compare: SharedComparator<'a, I::Item>,
+    /// The next element of every source that still has elements, along with the
+    /// index of that source.
+    the_heap: Heap<(I::Item, usize), MergeIterComparator<'a, I::Item>>,
+    dedup: bool,
+}
+
+/// The comparator that a [`MergeIter`] shares with its heap.
+type SharedComparator<'a, T> = Rc<dyn Fn(&T, &T) -> Ordering + 'a>;
+
+/// The comparator that orders the heads of the sources in a [`MergeIter`].
+type MergeIterComparator<'a, T> = Box<dyn Fn(&(T, usize), &(T, usize)) -> Ordering + 'a>;
+
+impl<'a, I> MergeIter<'a, I>
+where
+    I: Iterator,
+{
+    /// Creates a new `MergeIter`, pulling the first element out of every source.
+    ///
+    /// # Params
+    /// - `sources` - The iterators to merge (or anything that turns into them), each already
+    ///   sorted by `compare`.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    pub fn new<S, F>(sources: S, compare: F) -> Self
+    where
+        S: IntoIterator,
+        S::Item: IntoIterator<IntoIter = I, Item = I::Item>,
+        I::Item: 'a,
+        F: Fn(&I::Item, &I::Item) -> Ordering + 'a, // we want a closure to compare the two values and return an ordering
+    {
+        let compare: SharedComparator<'a, I::Item> = Rc::new(compare);
+
+        // equal elements are taken from the earliest source first to keep the merge stable
+        let heap_compare = Rc::clone(&compare);
+        let mut merge_iter = Self {
+            sources: sources.into_iter().map(IntoIterator::into_iter).collect(),
+            compare,
+            the_heap: Heap::with_comparator(Box::new(move |first, second| {
+                heap_compare(&first.0, &second.0).then(first.1.cmp(&second.1))
+            })),
+            dedup: false,
+        };
+        for source_idx in 0..merge_iter.sources.len() {
+            merge_iter.refill(source_idx);
+        }
+
+        merge_iter
     }
 
-    loop {
-        let element_to_add = find_next_element(&mut indicies, in_order);
+    /// Makes the iterator skip elements that are equal to the element it just returned,
+    /// keeping only the first of every run of equal elements.
+    pub fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
 
-        match element_to_add {
-            None => {
-                // break from the loop and cause the resulting merged list to be returned
-                break;
-            }
-            Some(element) => {
-                merged.push(element);
+    /// Pulls the next element out of a source and into the heap, if it has one.
+    fn refill(&mut self, source_idx: usize) {
+        if let Some(element) = self.sources[source_idx].next() {
+            self.the_heap.push((element, source_idx));
+        }
+    }
+}
+
+impl<I> Iterator for MergeIter<'_, I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (element, source_idx) = self.the_heap.pop()?;
+        self.refill(source_idx);
+
+        if self.dedup {
+            // every source is sorted, so all the duplicates of this element are next in the heap
+            while let Some((next_element, _)) = self.the_heap.peek() {
+                if (self.compare)(next_element, &element) != Ordering::Equal {
+                    break;
+                }
+
+                let (_, next_source_idx) = self.the_heap.pop()?;
+                self.refill(next_source_idx);
             }
         }
+
+        Some(element)
     }
 
-    merged
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut upper = Some(self.the_heap.len());
+        let mut lower = self.the_heap.len();
+        for source in &self.sources {
+            let (source_lower, source_upper) = source.size_hint();
+            lower = lower.saturating_add(source_lower);
+            upper = upper
+                .zip(source_upper)
+                .and_then(|(sum, add)| sum.checked_add(add));
+        }
+
+        if self.dedup {
+            // everything left could be a duplicate of the first element
+            lower = usize::min(lower, 1);
+        }
+
+        (lower, upper)
+    }
 }
 
 /// Finds the element to add (such as a minimum or maximum) value from a `Vec` of `Vec`s.
 ///
 /// # Params
 /// - `indices` - A `Vec` of tuples, containing the (current index, list for that index). Keeps track of
-/// the current index into that `Vec` and also the `Vec` itself.
-/// - `in_order` - The closure that determines if its `first` argument comes before its `second` argument.
+///   the current index into that `Vec` and also the `Vec` itself.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
 ///
 /// # Returns
 /// - `Some(T)` when successfully finding the next element or `None` upon failure.
-fn find_next_element<T, U>(indices: &mut Vec<(usize, &Vec<T>)>, in_order: &U) -> Option<T>
+///
+/// # Notes
+/// - When elements are equal, the one from the earliest list is taken, which keeps merging stable.
+fn find_next_element<T, F>(indices: &mut [(usize, &[T])], compare: &F) -> Option<T>
 where
     T: Clone,
-    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     // add all of the elements to the merged vec
     let mut element_to_add: Option<&T> = None;
     let mut element_list_idx: Option<usize> = None;
-    for i in 0..indices.len() {
-        let (idx, list) = indices[i];
+    for (i, &(idx, list)) in indices.iter().enumerate() {
         if idx < list.len() {
-            // if we haven't taken an element or the current element is out of the order we want
+            // if we haven't taken an element or the current element comes strictly before it
             if element_to_add.is_none()
-                || !in_order(
-                    element_to_add.expect("Attempted to compare None value with element"),
+                || compare(
                     &list[idx],
-                )
+                    element_to_add.expect("Attempted to compare None value with element"),
+                ) == Ordering::Less
             {
                 // this is our new element to add
                 element_to_add = Some(&list[idx]);
@@ -147,7 +587,7 @@ where
         }
     }
 
-    let output = match element_to_add {
+    match element_to_add {
         None => None,
         Some(element) => {
             match element_list_idx {
@@ -162,52 +602,416 @@ where
                 }
             }
         }
-    };
-
-    output
+    }
 }
 
+/// Performs a multithreaded merge sort on a list of elements.
+///
+/// The list is split into one chunk per thread, and every chunk is sorted on its own
+/// thread. The sorted chunks are then merged pairwise in a tree: every round merges
+/// neighbouring chunks on their own threads, so there are only `log2(num_threads)`
+/// rounds of merging and all but the last one run in parallel. The sort is stable.
+///
+/// The jobs are scoped to this call, so the list is only borrowed and `in_order` can
+/// capture local variables. The sort runs on the [`default_pool`], which every call
+/// shares. Use [`merge_sort_top_down_pooled`] to run it on a pool of your own instead.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+/// - `num_threads` - The number of chunks to sort in parallel, which is the most threads
+///   the sort uses at once.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, [`SortError::InvalidThreadCount`] if `num_threads`
+///   is zero, or [`SortError::WorkerPanicked`] if a thread panicked.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_top_down_multithread;
+///
+/// // sort rows by a column that is only known at runtime
+/// let column = 1;
+/// let mut rows = vec![vec![1, 9], vec![2, 3], vec![3, 5]];
+/// merge_sort_top_down_multithread(&mut rows, &|row1: &Vec<i32>, row2: &Vec<i32>| {
+///     row1[column] <= row2[column]
+/// }, 2)
+/// .expect("Error when doing multithreaded merge sort");
+/// assert_eq!(vec![vec![2, 3], vec![3, 5], vec![1, 9]], rows);
+///
+/// ```
 pub fn merge_sort_top_down_multithread<T, U>(
-    list: Vec<T>,
-    in_order: &'static U,
+    list: &mut [T],
+    in_order: &U,
+    num_threads: u32,
+) -> Result<(), SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    merge_sort_top_down_multithread_by(
+        list,
+        |first, second| ordering_from_in_order(in_order, first, second),
+        num_threads,
+    )
+}
+
+/// Performs a multithreaded merge sort on a list of elements, using a closure that returns
+/// an `Ordering`. See [`merge_sort_top_down_multithread`] for how the work is split up.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `num_threads` - The number of chunks to sort in parallel, which is the most threads
+///   the sort uses at once.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, [`SortError::InvalidThreadCount`] if `num_threads`
+///   is zero, or [`SortError::WorkerPanicked`] if a thread panicked.
+pub fn merge_sort_top_down_multithread_by<T, F>(
+    list: &mut [T],
+    compare: F,
     num_threads: u32,
-) -> Result<Vec<T>, &'static str>
+) -> Result<(), SortError>
 where
-    T: Clone + Send + Sync + 'static, // we want to be able to clone the datatype held in the vector
-    U: Fn(&T, &T) -> bool + Sync + Send + Clone, // we want a closure to compare the two values and return a bool
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
 {
     if num_threads == 0 {
-        return Err("Cannot perform a merge sort with no threads");
+        return Err(SortError::InvalidThreadCount);
     }
 
-    let mut handlers = vec![];
-    let work = list.len() / num_threads as usize;
+    merge_sort_chunks_on_pool(list, compare, default_pool()?, num_threads as usize)
+}
 
-    for i in 0..num_threads {
-        // create a thread
-        let start = i as usize * work;
-        let end = if i == num_threads - 1 {
-            list.len()
-        } else {
-            start + work
-        };
+/// Performs a multithreaded merge sort on a list of elements, picking the number of
+/// threads with [`auto_thread_count`]. Short lists are sorted on the calling thread.
+/// Like [`merge_sort_top_down_multithread`], the sort runs on the [`default_pool`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Returns
+/// - The thread configuration that was used once the list is sorted, or
+///   [`SortError::WorkerPanicked`] if a thread panicked.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::mergesort::merge_sort_top_down_multithread_auto;
+///
+/// let mut list: Vec<i32> = (0..100_000).rev().collect();
+/// let report = merge_sort_top_down_multithread_auto(&mut list, &|num1: &i32, num2: &i32| {
+///     num1 <= num2
+/// })
+/// .expect("Error when doing multithreaded merge sort");
+/// assert_eq!((0..100_000).collect::<Vec<i32>>(), list);
+/// println!("sorted with {} threads", report.num_threads);
+///
+/// ```
+pub fn merge_sort_top_down_multithread_auto<T, U>(
+    list: &mut [T],
+    in_order: &U,
+) -> Result<ThreadCountReport, SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    merge_sort_top_down_multithread_auto_by(list, |first, second| {
+        ordering_from_in_order(in_order, first, second)
+    })
+}
 
-        let new_list = list[start..end].to_vec();
-        handlers.push(thread::spawn(move || {
-            merge_sort_top_down(&new_list, in_order)
-        }));
+/// Performs a multithreaded merge sort on a list of elements, picking the number of
+/// threads with [`auto_thread_count`] and using a closure that returns an `Ordering`.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The thread configuration that was used once the list is sorted, or
+///   [`SortError::WorkerPanicked`] if a thread panicked.
+pub fn merge_sort_top_down_multithread_auto_by<T, F>(
+    list: &mut [T],
+    compare: F,
+) -> Result<ThreadCountReport, SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    let report = auto_thread_count(list.len());
+    if report.num_threads == 1 {
+        // no point in spinning up a pool for a single thread
+        SortError::catch_panic(|| merge_sort_bottom_up_by(list, compare))?;
+    } else {
+        merge_sort_top_down_multithread_by(list, compare, report.num_threads)?;
     }
 
-    let mut merged = vec![];
-    for handler in handlers {
-        let result = handler.join();
-        match result {
-            Ok(the_vec) => merged = merge_vecs(&merged, &the_vec, in_order),
-            Err(_) => {
-                return Err("Error when joining thread in merge sort");
+    Ok(report)
+}
+
+/// Performs a multithreaded merge sort on a list of elements, running the work on the
+/// workers of a [`SortPool`] of your own instead of the [`default_pool`].
+///
+/// The list is split into one chunk per worker in the pool. Otherwise, the work is split
+/// up the same way as in [`merge_sort_top_down_multithread`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+/// - `pool` - The pool to run the sort on.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+pub fn merge_sort_top_down_pooled<T, U>(
+    list: &mut [T],
+    in_order: &U,
+    pool: &SortPool,
+) -> Result<(), SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    merge_sort_top_down_pooled_by(
+        list,
+        |first, second| ordering_from_in_order(in_order, first, second),
+        pool,
+    )
+}
+
+/// Performs a multithreaded merge sort on a list of elements on the workers of a
+/// [`SortPool`], using a closure that returns an `Ordering`.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `pool` - The pool to run the sort on.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+pub fn merge_sort_top_down_pooled_by<T, F>(
+    list: &mut [T],
+    compare: F,
+    pool: &SortPool,
+) -> Result<(), SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    merge_sort_chunks_on_pool(list, compare, pool, pool.num_threads() as usize)
+}
+
+/// Sorts every chunk of the list as its own job on a pool, and then merges the chunks
+/// pairwise in a tree. Helper function for merge_sort_top_down_multithread and
+/// merge_sort_top_down_pooled.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `pool` - The pool to run the sort on.
+/// - `num_chunks` - The number of chunks to split the list into. Must not be zero.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+fn merge_sort_chunks_on_pool<T, F>(
+    list: &mut [T],
+    compare: F,
+    pool: &SortPool,
+    num_chunks: usize,
+) -> Result<(), SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    // there's no use in more chunks than elements
+    let num_chunks = usize::min(num_chunks, usize::max(list.len(), 1));
+
+    // find where each chunk starts, with the last chunk taking whatever is left over
+    let work = list.len() / num_chunks;
+    let mut run_starts: Vec<usize> = (0..num_chunks).map(|i| i * work).collect();
+    run_starts.dedup();
+
+    // sort every chunk as its own job
+    pool.scope(|scope| {
+        for chunk in split_into_runs(list, &run_starts) {
+            let compare = &compare;
+            scope.spawn(move || merge_sort_bottom_up_by(chunk, compare));
+        }
+    })?;
+
+    // merge neighbouring runs in parallel until only one is left
+    while run_starts.len() > 1 {
+        // every other run start disappears, as that run is merged into the one before it
+        let merged_starts: Vec<usize> = run_starts.iter().copied().step_by(2).collect();
+
+        pool.scope(|scope| {
+            for (pair_idx, pair) in split_into_runs(list, &merged_starts)
+                .into_iter()
+                .enumerate()
+            {
+                // the start of the second run in the pair, if there is one
+                let Some(&mid) = run_starts.get(pair_idx * 2 + 1) else {
+                    // an odd run out waits for the next round
+                    continue;
+                };
+                let mid = mid - merged_starts[pair_idx];
+                let compare = &compare;
+                scope.spawn(move || {
+                    let mut buffer = Vec::with_capacity(usize::min(mid, pair.len() - mid));
+                    merge_with_buffer(pair, mid, &mut buffer, compare);
+                });
             }
+        })?;
+
+        run_starts = merged_starts;
+    }
+
+    Ok(())
+}
+
+/// Splits a list into separate mutable runs, so that each one can be handed to a different thread.
+///
+/// # Params
+/// - `list` - The slice to split.
+/// - `run_starts` - The index that each run starts at, in increasing order, starting at `0`.
+///
+/// # Returns
+/// - The runs, with the last one going until the end of the list.
+fn split_into_runs<'a, T>(mut list: &'a mut [T], run_starts: &[usize]) -> Vec<&'a mut [T]> {
+    let mut runs = vec![];
+    for (i, &start) in run_starts.iter().enumerate().skip(1) {
+        let (run, rest) = list.split_at_mut(start - run_starts[i - 1]);
+        runs.push(run);
+        list = rest;
+    }
+    runs.push(list);
+
+    runs
+}
+
+/// [`Sorter`] for [`merge_sort_top_down_multithread`].
+#[derive(Debug, Clone, Copy)]
+pub struct MergeSortMultithread {
+    /// The number of threads to sort with. Must not be zero.
+    pub num_threads: u32,
+}
+
+impl<T: Send> Sorter<T> for MergeSortMultithread {
+    fn name(&self) -> &'static str {
+        "Merge sort (multithreaded)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: false,
+            parallel: true,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        if let Err(err_val) = self.try_sort_by(list, compare) {
+            panic!("Error when doing multithreaded merge sort ({})", err_val);
+        }
+    }
+
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError> {
+        merge_sort_top_down_multithread_by(list, compare, self.num_threads)
+    }
+}
+
+/// [`Sorter`] for [`merge_sort_top_down_pooled`].
+#[derive(Debug, Clone, Copy)]
+pub struct MergeSortPooled<'a> {
+    /// The pool to run the sort on.
+    pub pool: &'a SortPool,
+}
+
+impl<T: Send> Sorter<T> for MergeSortPooled<'_> {
+    fn name(&self) -> &'static str {
+        "Merge sort (pooled)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: false,
+            parallel: true,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        if let Err(err_val) = self.try_sort_by(list, compare) {
+            panic!("Error when doing pooled merge sort ({})", err_val);
         }
     }
 
-    Ok(merged)
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError> {
+        merge_sort_top_down_pooled_by(list, compare, self.pool)
+    }
+}
+
+/// [`Sorter`] for [`merge_sort_top_down`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeSort;
+
+impl<T: Clone> Sorter<T> for MergeSort {
+    fn name(&self) -> &'static str {
+        "Merge sort (top down)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: false,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        merge_sort_top_down_by(list, compare);
+    }
+}
+
+/// [`Sorter`] for [`merge_sort_bottom_up`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeSortBottomUp;
+
+impl<T> Sorter<T> for MergeSortBottomUp {
+    fn name(&self) -> &'static str {
+        "Merge sort (bottom up)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: false,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        merge_sort_bottom_up_by(list, compare);
+    }
 }
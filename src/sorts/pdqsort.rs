@@ -0,0 +1,618 @@
+use std::cmp::Ordering;
+
+use crate::sorts::{
+    heapsort::heap_sort_by, insertionsort::insertion_sort_by, ordering_from_in_order, Capabilities,
+    Sorter,
+};
+
+/// Performs a pattern-defeating quicksort (pdqsort) on a list of elements.
+///
+/// This is a quicksort that looks out for the kinds of input that real data tends to have:
+/// - Partitions are split in blocks, which decides where every element in a block goes
+///   before moving any of them, and keeps the comparisons free of hard to predict branches.
+/// - If a partition needed no swaps and the pivot looked like it came from sorted data,
+///   a partial insertion sort is tried, which finishes off nearly sorted data in linear time.
+/// - If the pivot is equal to the one before it, all of the elements equal to it are
+///   split off at once, so lots of duplicates make the sort faster instead of slower.
+/// - Badly unbalanced partitions get a few elements shuffled around to break up whatever
+///   pattern is defeating the pivot choice, and after too many of them the partition is
+///   finished off with a heapsort, so the worst case stays at `O(n log n)`.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::pdqsort::pdq_sort;
+///
+/// // create a list of elements
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort using a closure to sort elements in ascending order
+/// pdq_sort(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn pdq_sort<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    pdq_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a pattern-defeating quicksort on a list of elements, using a closure that returns
+/// an `Ordering`. The list is sorted in place, without any allocations or clones. The sort is
+/// not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::pdqsort::pdq_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// pdq_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn pdq_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // the number of unbalanced partitions allowed before switching to a heapsort
+    let limit = usize::BITS - list.len().leading_zeros();
+
+    pdq_sort_recursive(list, &compare, None, limit);
+}
+
+/// Performs a pattern-defeating quicksort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn pdq_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    pdq_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a pattern-defeating quicksort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn pdq_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    pdq_sort_by(list, T::cmp);
+}
+
+/// Performs a recursive pattern-defeating quicksort in place. Helper function for pdq_sort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `pred` - The pivot that the partition to the left of `list` was split off with, if
+///   there is one. Nothing in `list` comes before it.
+/// - `limit` - How many more unbalanced partitions are allowed before switching to a heapsort.
+///
+/// # Notes
+/// - Only the smaller partition is recursed into, while the larger one is handled by the
+///   loop, which keeps the recursion depth at `O(log n)`.
+fn pdq_sort_recursive<'a, T, F>(
+    mut list: &'a mut [T],
+    compare: &F,
+    mut pred: Option<&'a T>,
+    mut limit: u32,
+) where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // lists this short are insertion sorted
+    static MAX_INSERTION: usize = 20;
+
+    // whether the last partition was reasonably balanced, and whether it needed no swaps
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = list.len();
+        if len <= MAX_INSERTION {
+            insertion_sort_by(list, compare);
+            return;
+        }
+
+        // too many bad pivots, so make sure this still takes `O(n log n)`
+        if limit == 0 {
+            heap_sort_by(list, compare);
+            return;
+        }
+
+        // shuffle some elements around if the last partition was unbalanced, hoping to
+        // break up the pattern that caused it
+        if !was_balanced {
+            break_patterns(list);
+            limit -= 1;
+        }
+
+        let (pivot_idx, likely_sorted) = choose_pivot(list, compare);
+
+        // the list looks like it is already sorted, so try to finish it off cheaply
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(list, compare)
+        {
+            return;
+        }
+
+        // if the pivot is equal to the previous one, it is the smallest element in the list,
+        // so split off everything equal to it instead of sorting duplicates over and over
+        if let Some(pred) = pred {
+            if compare(pred, &list[pivot_idx]) != Ordering::Less {
+                let mid = partition_equal(list, pivot_idx, compare);
+                let (_, right_list) = list.split_at_mut(mid);
+                list = right_list;
+                continue;
+            }
+        }
+
+        let (mid, already_partitioned) = partition(list, pivot_idx, compare);
+        was_balanced = usize::min(mid, len - mid) >= len / 8;
+        was_partitioned = already_partitioned;
+
+        // the pivot is already in place, so it isn't part of either partition
+        let (left_list, right_list) = list.split_at_mut(mid);
+        let (pivot, right_list) = right_list.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        if left_list.len() < right_list.len() {
+            pdq_sort_recursive(left_list, compare, pred, limit);
+            list = right_list;
+            pred = Some(pivot);
+        } else {
+            pdq_sort_recursive(right_list, compare, Some(pivot), limit);
+            list = left_list;
+        }
+    }
+}
+
+/// Chooses a pivot, and reverses the list if it looks like it is in descending order.
+///
+/// Short lists use the median of three elements, and longer ones the median of the medians
+/// of three groups of three (Tukey's ninther). The number of swaps needed to find the median
+/// also says something about the order of the list.
+///
+/// # Params
+/// - `list` - The slice to choose a pivot from.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The index of the pivot, and whether the list looks like it is already sorted.
+fn choose_pivot<T, F>(list: &mut [T], compare: &F) -> (usize, bool)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // lists at least this long use the ninther
+    static SHORTEST_NINTHER: usize = 50;
+    // the most swaps that finding the median can take
+    static MAX_SWAPS: usize = 4 * 3;
+
+    let len = list.len();
+
+    // three indices spread across the list
+    let mut first = len / 4;
+    let mut second = len / 4 * 2;
+    let mut third = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        if len >= SHORTEST_NINTHER {
+            // replace each index with the median of it and its neighbours
+            for idx in [&mut first, &mut second, &mut third] {
+                let (mut before, mut after) = (*idx - 1, *idx + 1);
+                sort_three_indices(list, compare, &mut before, idx, &mut after, &mut swaps);
+            }
+        }
+
+        sort_three_indices(
+            list,
+            compare,
+            &mut first,
+            &mut second,
+            &mut third,
+            &mut swaps,
+        );
+    }
+
+    if swaps < MAX_SWAPS {
+        (second, swaps == 0)
+    } else {
+        // every comparison needed a swap, so the list is most likely in descending order
+        list.reverse();
+        (len - 1 - second, true)
+    }
+}
+
+/// Reorders three indices so that the elements they point to are in order. Only the
+/// indices are swapped, never the elements.
+///
+/// # Params
+/// - `list` - The slice that the indices point into.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `first`, `second`, `third` - The indices to reorder.
+/// - `swaps` - The number of swaps made so far, which is added to.
+fn sort_three_indices<T, F>(
+    list: &[T],
+    compare: &F,
+    first: &mut usize,
+    second: &mut usize,
+    third: &mut usize,
+    swaps: &mut usize,
+) where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // we want a closure to swap two indices if their elements are out of order
+    let mut sort_two_indices = |low: &mut usize, high: &mut usize| {
+        if compare(&list[*high], &list[*low]) == Ordering::Less {
+            std::mem::swap(low, high);
+            *swaps += 1;
+        }
+    };
+
+    sort_two_indices(first, second);
+    sort_two_indices(second, third);
+    sort_two_indices(first, second);
+}
+
+/// Partitions the list around the pivot at `pivot_idx`.
+///
+/// # Params
+/// - `list` - The slice to partition.
+/// - `pivot_idx` - The index of the pivot.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The index that the pivot ended up at, with every element before it coming before the
+///   pivot and every element after it not coming before the pivot. Also whether the list
+///   was already partitioned, meaning no elements had to be moved.
+fn partition<T, F>(list: &mut [T], pivot_idx: usize, compare: &F) -> (usize, bool)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // move the pivot out of the way, to the front of the list
+    list.swap(0, pivot_idx);
+    let (pivot, rest) = list.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    // skip past the elements at either end that are already on the right side
+    let (mut left, mut right) = (0, rest.len());
+    while left < right && compare(&rest[left], pivot) == Ordering::Less {
+        left += 1;
+    }
+    while left < right && compare(&rest[right - 1], pivot) != Ordering::Less {
+        right -= 1;
+    }
+
+    let mid = left + partition_in_blocks(&mut rest[left..right], pivot, compare);
+    let already_partitioned = left >= right;
+
+    // put the pivot between the two partitions
+    list.swap(0, mid);
+
+    (mid, already_partitioned)
+}
+
+/// Partitions a list into the elements that come before `pivot`, followed by the ones that
+/// don't, using the block partitioning from BlockQuicksort.
+///
+/// A block of elements from each end of the list is compared against the pivot, and the
+/// offsets of the elements on the wrong side are written down. The elements at those offsets
+/// are then swapped pairwise. Comparing a whole block at once keeps the comparisons apart
+/// from the swaps, which avoids a mispredicted branch for every element.
+///
+/// # Params
+/// - `list` - The slice to partition. Must not contain the pivot.
+/// - `pivot` - The pivot to partition around.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The number of elements that come before the pivot.
+fn partition_in_blocks<T, F>(list: &mut [T], pivot: &T, compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // the number of elements in a block, which has to fit the offsets in a `u8`
+    static BLOCK: usize = 128;
+
+    // everything before `left` comes before the pivot, and everything from `right` on doesn't
+    let mut left = 0;
+    let mut right = list.len();
+
+    // offsets from `left` of elements in the left block that don't come before the pivot
+    let mut offsets_left = [0u8; BLOCK];
+    let (mut start_left, mut end_left) = (0, 0);
+    let mut block_left = BLOCK;
+
+    // offsets back from `right` of elements in the right block that do come before the pivot
+    let mut offsets_right = [0u8; BLOCK];
+    let (mut start_right, mut end_right) = (0, 0);
+    let mut block_right = BLOCK;
+
+    loop {
+        // when there is room for two blocks or less, this is the last round
+        let is_done = right - left <= 2 * BLOCK;
+
+        if is_done {
+            // shrink the blocks to exactly cover what is left. A block that still has
+            // offsets waiting to be swapped keeps its size
+            let mut remaining = right - left;
+            if start_left < end_left || start_right < end_right {
+                remaining -= BLOCK;
+            }
+
+            if start_left < end_left {
+                block_right = remaining;
+            } else if start_right < end_right {
+                block_left = remaining;
+            } else {
+                block_left = remaining / 2;
+                block_right = remaining - block_left;
+            }
+        }
+
+        // scan a new left block if the last one is used up
+        if start_left == end_left {
+            start_left = 0;
+            end_left = 0;
+            for offset in 0..block_left {
+                offsets_left[end_left] = offset as u8;
+                end_left += (compare(&list[left + offset], pivot) != Ordering::Less) as usize;
+            }
+        }
+
+        // scan a new right block if the last one is used up
+        if start_right == end_right {
+            start_right = 0;
+            end_right = 0;
+            for offset in 0..block_right {
+                offsets_right[end_right] = offset as u8;
+                end_right += (compare(&list[right - 1 - offset], pivot) == Ordering::Less) as usize;
+            }
+        }
+
+        // swap elements on the wrong side in pairs
+        let count = usize::min(end_left - start_left, end_right - start_right);
+        for k in 0..count {
+            list.swap(
+                left + offsets_left[start_left + k] as usize,
+                right - 1 - offsets_right[start_right + k] as usize,
+            );
+        }
+        start_left += count;
+        start_right += count;
+
+        // move past any block that is fully in place
+        if start_left == end_left {
+            left += block_left;
+        }
+        if start_right == end_right {
+            right -= block_right;
+        }
+
+        if is_done {
+            break;
+        }
+    }
+
+    // at most one block has elements left on the wrong side, and it is the only block
+    // left, so move them to the far end of it
+    if start_left < end_left {
+        while start_left < end_left {
+            end_left -= 1;
+            right -= 1;
+            list.swap(left + offsets_left[end_left] as usize, right);
+        }
+        right
+    } else if start_right < end_right {
+        while start_right < end_right {
+            end_right -= 1;
+            list.swap(left, right - 1 - offsets_right[end_right] as usize);
+            left += 1;
+        }
+        left
+    } else {
+        left
+    }
+}
+
+/// Moves every element equal to the pivot at `pivot_idx` to the front of the list. Nothing
+/// in the list may come before the pivot.
+///
+/// # Params
+/// - `list` - The slice to partition.
+/// - `pivot_idx` - The index of the pivot.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The number of elements equal to the pivot, including the pivot itself.
+fn partition_equal<T, F>(list: &mut [T], pivot_idx: usize, compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // move the pivot out of the way, to the front of the list
+    list.swap(0, pivot_idx);
+    let (pivot, rest) = list.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    let (mut left, mut right) = (0, rest.len());
+    loop {
+        // nothing comes before the pivot, so not coming after it means being equal to it
+        while left < right && compare(pivot, &rest[left]) != Ordering::Less {
+            left += 1;
+        }
+        while left < right && compare(pivot, &rest[right - 1]) == Ordering::Less {
+            right -= 1;
+        }
+
+        if left >= right {
+            break;
+        }
+
+        right -= 1;
+        rest.swap(left, right);
+        left += 1;
+    }
+
+    // count the pivot as well
+    left + 1
+}
+
+/// Sorts a list that is almost sorted, by fixing a few out of order elements.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - `true` if the list ended up sorted, or `false` if there were too many elements out of
+///   order. Either way, the list holds the same elements.
+fn partial_insertion_sort<T, F>(list: &mut [T], compare: &F) -> bool
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // the most out of order pairs to fix before giving up
+    static MAX_STEPS: usize = 5;
+    // shorter lists aren't worth shifting elements in, as they get sorted quickly anyway
+    static SHORTEST_SHIFTING: usize = 50;
+
+    let len = list.len();
+    let mut i = 1;
+
+    for _ in 0..MAX_STEPS {
+        // find the next pair of elements that are out of order
+        while i < len && compare(&list[i], &list[i - 1]) != Ordering::Less {
+            i += 1;
+        }
+
+        if i == len {
+            return true;
+        }
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        // swap the pair, then move each of them along until they are in place
+        list.swap(i - 1, i);
+        shift_tail(&mut list[..i], compare);
+        shift_head(&mut list[i..], compare);
+    }
+
+    false
+}
+
+/// Moves the last element of a list towards the front until it is in order, assuming the
+/// rest of the list is sorted.
+///
+/// # Params
+/// - `list` - The slice to fix up.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+fn shift_tail<T, F>(list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let mut i = list.len();
+    while i > 1 && compare(&list[i - 1], &list[i - 2]) == Ordering::Less {
+        list.swap(i - 1, i - 2);
+        i -= 1;
+    }
+}
+
+/// Moves the first element of a list towards the back until it is in order, assuming the
+/// rest of the list is sorted.
+///
+/// # Params
+/// - `list` - The slice to fix up.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+fn shift_head<T, F>(list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let mut i = 0;
+    while i + 1 < list.len() && compare(&list[i + 1], &list[i]) == Ordering::Less {
+        list.swap(i, i + 1);
+        i += 1;
+    }
+}
+
+/// Swaps a few elements around the middle of the list with ones at random positions, to
+/// break up patterns that make for bad pivots.
+///
+/// # Params
+/// - `list` - The slice to shuffle.
+fn break_patterns<T>(list: &mut [T]) {
+    let len = list.len();
+    if len < 8 {
+        return;
+    }
+
+    // a xorshift seeded with the length, so the sort is the same every time it is run
+    let mut random = len as u32;
+    let mut next_random = || {
+        random ^= random << 13;
+        random ^= random >> 17;
+        random ^= random << 5;
+        random as usize
+    };
+
+    // the random numbers just have to be below the next power of two
+    let modulus = len.next_power_of_two();
+    let middle = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = next_random() & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        list.swap(middle - 1 + i, other);
+    }
+}
+
+/// [`Sorter`] for [`pdq_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdqSort;
+
+impl<T> Sorter<T> for PdqSort {
+    fn name(&self) -> &'static str {
+        "Pattern-defeating quicksort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        pdq_sort_by(list, compare);
+    }
+}
@@ -0,0 +1,432 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::sorts::{available_parallelism, error::SortError};
+
+/// A job waiting in one of the queues of a [`SortPool`].
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    // the pool (by the address of its shared state) and the index of the worker that is
+    // running on this thread, if this is a pool thread
+    static CURRENT_WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// A pool of worker threads that the parallel sorts run their work on.
+///
+/// Spawning threads is expensive compared to sorting a small or medium sized list, so
+/// a pool can be created once and handed to every parallel sort, which then reuses its
+/// workers instead of spawning new ones. The pool can be shared between threads, and
+/// sorts that run on it at the same time just queue up their work.
+///
+/// Every worker has its own queue. A job spawned from a worker goes on the back of that
+/// worker's queue, and the worker takes its next job from the back too, so it keeps working
+/// on the partitions it just split off while they are still in its cache. Jobs spawned from
+/// any other thread go on a shared queue. A worker that runs out of jobs takes one from the
+/// shared queue, or failing that steals the oldest job from the front of another worker's
+/// queue, which tends to be the biggest piece of work that worker has waiting.
+///
+/// The workers are shut down when the pool is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::{mergesort::merge_sort_top_down_pooled, pool::SortPool};
+///
+/// let pool = SortPool::new(4).expect("Error when creating the sort pool");
+/// // every sort reuses the same four workers
+/// for _ in 0..10 {
+///     let mut list: Vec<i32> = (0..10_000).rev().collect();
+///     merge_sort_top_down_pooled(&mut list, &|num1: &i32, num2: &i32| num1 <= num2, &pool)
+///         .expect("Error when doing pooled merge sort");
+///     assert_eq!((0..10_000).collect::<Vec<i32>>(), list);
+/// }
+///
+/// ```
+pub struct SortPool {
+    shared: Arc<PoolShared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// The state shared between a [`SortPool`] and its workers.
+struct PoolShared {
+    /// The queue for jobs spawned from threads outside of the pool.
+    queue: Mutex<PoolQueue>,
+    /// The queue of each worker, for the jobs spawned from that worker.
+    worker_queues: Vec<Mutex<VecDeque<Job>>>,
+    /// The number of jobs waiting across every queue. Only ever changed while a queue is
+    /// locked, and always increased before a sleeping worker is notified.
+    num_queued: AtomicUsize,
+    /// Notified whenever a job is added or the pool is shutting down.
+    job_available: Condvar,
+}
+
+struct PoolQueue {
+    jobs: VecDeque<Job>,
+    shutting_down: bool,
+}
+
+impl PoolShared {
+    /// An id for the pool, which is the same for the pool and all of its workers.
+    fn id(self: &Arc<Self>) -> usize {
+        Arc::as_ptr(self) as usize
+    }
+
+    /// The index of the worker running on this thread, if this thread is one of the workers.
+    fn current_worker(self: &Arc<Self>) -> Option<usize> {
+        match CURRENT_WORKER.get() {
+            Some((pool_id, worker_idx)) if pool_id == self.id() => Some(worker_idx),
+            _ => None,
+        }
+    }
+
+    /// Takes the next job to run, looking at the worker's own queue first, then the shared
+    /// queue, and then stealing from the other workers.
+    ///
+    /// # Params
+    /// - `worker_idx` - The index of the worker looking for a job, if it is one.
+    ///
+    /// # Returns
+    /// - The job, or `None` if every queue was empty.
+    fn find_job(&self, worker_idx: Option<usize>) -> Option<Job> {
+        let num_workers = self.worker_queues.len();
+        let own_job = worker_idx.and_then(|idx| lock(&self.worker_queues[idx]).pop_back());
+        let job = own_job
+            .or_else(|| lock(&self.queue).jobs.pop_front())
+            .or_else(|| {
+                // start with the worker after this one, so the workers don't all try to
+                // steal from the same one
+                let first = worker_idx.map_or(0, |idx| idx + 1);
+                (first..first + num_workers)
+                    .map(|victim| victim % num_workers)
+                    .filter(|&victim| Some(victim) != worker_idx)
+                    .find_map(|victim| lock(&self.worker_queues[victim]).pop_front())
+            })?;
+
+        self.num_queued.fetch_sub(1, AtomicOrdering::SeqCst);
+        Some(job)
+    }
+}
+
+impl SortPool {
+    /// Creates a new pool and starts its workers.
+    ///
+    /// # Params
+    /// - `num_threads` - The number of worker threads in the pool.
+    ///
+    /// # Returns
+    /// - The new pool, or `Err` if `num_threads` is zero or a worker couldn't be spawned.
+    pub fn new(num_threads: u32) -> Result<SortPool, SortError> {
+        if num_threads == 0 {
+            return Err(SortError::InvalidThreadCount);
+        }
+
+        let shared = Arc::new(PoolShared {
+            queue: Mutex::new(PoolQueue {
+                jobs: VecDeque::new(),
+                shutting_down: false,
+            }),
+            worker_queues: (0..num_threads)
+                .map(|_| Mutex::new(VecDeque::new()))
+                .collect(),
+            num_queued: AtomicUsize::new(0),
+            job_available: Condvar::new(),
+        });
+
+        // keep the pool around while spawning, so that the workers that did start get
+        // shut down if spawning a later one fails
+        let mut pool = SortPool {
+            shared,
+            workers: Vec::with_capacity(num_threads as usize),
+        };
+        for i in 0..num_threads {
+            let shared = Arc::clone(&pool.shared);
+            let worker = thread::Builder::new()
+                .name(format!("sort-pool-{}", i))
+                .spawn(move || run_worker(&shared, i as usize))
+                .map_err(|err_val| SortError::ThreadSpawnFailed(err_val.to_string()))?;
+            pool.workers.push(worker);
+        }
+
+        Ok(pool)
+    }
+
+    /// The number of worker threads in the pool.
+    pub fn num_threads(&self) -> u32 {
+        self.workers.len() as u32
+    }
+
+    /// Runs `f` with a scope that jobs can be spawned on, and waits for every one of those
+    /// jobs to finish before returning.
+    ///
+    /// Like [`std::thread::scope`], the jobs can borrow anything that outlives the call,
+    /// so a sort can hand out pieces of a borrowed list. While waiting, the calling
+    /// thread helps by running queued jobs itself.
+    ///
+    /// # Params
+    /// - `f` - The closure that spawns the jobs.
+    ///
+    /// # Returns
+    /// - What `f` returned, or [`SortError::WorkerPanicked`] with the message of the first
+    ///   job that panicked.
+    ///
+    /// # Notes
+    /// If `f` itself panics, the panic is passed on once the jobs have finished.
+    pub fn scope<'env, F, R>(&self, f: F) -> Result<R, SortError>
+    where
+        F: for<'scope> FnOnce(&'scope PoolScope<'scope, 'env>) -> R,
+    {
+        let scope = PoolScope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                counts: Mutex::new(ScopeCounts {
+                    pending: 0,
+                    panic: None,
+                }),
+                finished: Condvar::new(),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        let result = {
+            // wait for the jobs even if `f` panics, as they may borrow from the caller
+            let _guard = ScopeGuard { scope: &scope };
+            f(&scope)
+        };
+
+        if let Some(err_val) = lock(&scope.state.counts).panic.take() {
+            return Err(err_val);
+        }
+
+        Ok(result)
+    }
+
+    /// Adds a job to the queue of the worker spawning it, or to the shared queue if it
+    /// isn't spawned from a worker, and wakes up a worker to run it.
+    fn push_job(&self, job: Job) {
+        match self.shared.current_worker() {
+            Some(worker_idx) => {
+                let mut worker_queue = lock(&self.shared.worker_queues[worker_idx]);
+                worker_queue.push_back(job);
+                self.shared.num_queued.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+            None => {
+                let mut queue = lock(&self.shared.queue);
+                queue.jobs.push_back(job);
+                self.shared.num_queued.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+        }
+
+        // a worker checks for jobs with the shared queue locked before going to sleep, so
+        // locking it here makes sure that worker either saw the job or is already asleep
+        drop(lock(&self.shared.queue));
+        self.shared.job_available.notify_one();
+    }
+
+    /// Takes a job off one of the queues without waiting for one.
+    fn try_pop_job(&self) -> Option<Job> {
+        self.shared.find_job(self.shared.current_worker())
+    }
+}
+
+impl fmt::Debug for SortPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortPool")
+            .field("num_threads", &self.num_threads())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SortPool {
+    fn drop(&mut self) {
+        lock(&self.shared.queue).shutting_down = true;
+        self.shared.job_available.notify_all();
+
+        for worker in self.workers.drain(..) {
+            // jobs catch their own panics, so there is nothing to report here
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Gets the pool that the parallel sorts which aren't handed a [`SortPool`] run on, such
+/// as [`quick_sort_parallel`](crate::sorts::quicksort::quick_sort_parallel) and
+/// [`merge_sort_top_down_multithread`](crate::sorts::mergesort::merge_sort_top_down_multithread).
+///
+/// The pool is started the first time it's needed, with one worker for every thread the
+/// machine can run at once, and lives until the program exits. Every one of those sorts
+/// shares it, so the number of sorting threads stays the same no matter how many sorts
+/// run at the same time or how often they are called. Their `num_threads` only limits how
+/// many of the pool's threads a single sort keeps busy at once.
+///
+/// # Returns
+/// - The pool, or [`SortError::ThreadSpawnFailed`] if its workers couldn't be started.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::pool::default_pool;
+///
+/// let pool = default_pool().expect("Error when starting the default sort pool");
+/// // the same pool is handed out every time
+/// assert!(std::ptr::eq(pool, default_pool().unwrap()));
+///
+/// ```
+pub fn default_pool() -> Result<&'static SortPool, SortError> {
+    static DEFAULT_POOL: OnceLock<SortPool> = OnceLock::new();
+
+    if let Some(pool) = DEFAULT_POOL.get() {
+        return Ok(pool);
+    }
+
+    // if another thread got there first, its pool is kept and this one is shut down again
+    let pool = SortPool::new(available_parallelism())?;
+    Ok(DEFAULT_POOL.get_or_init(|| pool))
+}
+
+/// Runs jobs from the queues until the pool shuts down.
+///
+/// # Params
+/// - `shared` - The state shared with the pool.
+/// - `worker_idx` - The index of this worker's own queue.
+fn run_worker(shared: &Arc<PoolShared>, worker_idx: usize) {
+    CURRENT_WORKER.set(Some((shared.id(), worker_idx)));
+
+    loop {
+        if let Some(job) = shared.find_job(Some(worker_idx)) {
+            job();
+            continue;
+        }
+
+        let queue = lock(&shared.queue);
+        if queue.shutting_down {
+            return;
+        }
+        // only sleep if no job has been added since looking
+        drop(
+            shared
+                .job_available
+                .wait_while(queue, |queue| {
+                    !queue.shutting_down && shared.num_queued.load(AtomicOrdering::SeqCst) == 0
+                })
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+    }
+}
+
+/// A scope to spawn jobs on, created by [`SortPool::scope`].
+///
+/// `'scope` is how long the scope itself lives, and `'env` is what the jobs can borrow.
+pub struct PoolScope<'scope, 'env: 'scope> {
+    pool: &'scope SortPool,
+    state: Arc<ScopeState>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// Keeps track of the jobs spawned on a [`PoolScope`].
+struct ScopeState {
+    counts: Mutex<ScopeCounts>,
+    /// Notified when the last pending job finishes.
+    finished: Condvar,
+}
+
+struct ScopeCounts {
+    /// The number of jobs that have been spawned but haven't finished yet.
+    pending: usize,
+    /// The error for the first job that panicked, if any did.
+    panic: Option<SortError>,
+}
+
+impl<'scope> PoolScope<'scope, '_> {
+    /// Spawns a job on the pool. It will have finished by the time [`SortPool::scope`] returns.
+    ///
+    /// # Params
+    /// - `f` - The job to run.
+    pub fn spawn<F>(&'scope self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        lock(&self.state.counts).pending += 1;
+
+        let state = Arc::clone(&self.state);
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            // the panic is reported through the scope, so it must not take down the worker
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+
+            let mut counts = lock(&state.counts);
+            if let Err(payload) = result {
+                counts
+                    .panic
+                    .get_or_insert_with(|| SortError::from_panic(payload.as_ref()));
+            }
+            counts.pending -= 1;
+            if counts.pending == 0 {
+                state.finished.notify_all();
+            }
+        });
+
+        // SAFETY: `SortPool::scope` doesn't return (or unwind) until every job spawned on
+        // the scope has finished, so nothing the job borrows goes away while it can still run.
+        let job: Job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.push_job(job);
+    }
+
+    /// Blocks until every job spawned on the scope has finished, running queued jobs in
+    /// the meantime.
+    fn wait(&self) {
+        loop {
+            if lock(&self.state.counts).pending == 0 {
+                return;
+            }
+
+            // help out instead of sitting idle, which also keeps a busy pool from stalling us
+            if let Some(job) = self.pool.try_pop_job() {
+                job();
+                continue;
+            }
+
+            let counts = lock(&self.state.counts);
+            if counts.pending == 0 {
+                return;
+            }
+            // the workers have taken every queued job, so just wait for ours to finish
+            drop(
+                self.state
+                    .finished
+                    .wait_while(counts, |counts| counts.pending > 0)
+                    .unwrap_or_else(PoisonError::into_inner),
+            );
+        }
+    }
+}
+
+/// Waits for the jobs of a scope when dropped, so that they are waited for even if the
+/// closure passed to [`SortPool::scope`] panics.
+struct ScopeGuard<'a, 'scope, 'env> {
+    scope: &'a PoolScope<'scope, 'env>,
+}
+
+impl Drop for ScopeGuard<'_, '_, '_> {
+    fn drop(&mut self) {
+        self.scope.wait();
+    }
+}
+
+/// Locks a mutex of the pool. Jobs never run while one of these is locked, so a poisoned
+/// lock still holds consistent data.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -1,157 +1,940 @@
-use std::mem::swap;
+use std::{
+    cmp::Ordering,
+    mem::swap,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
 
-use crate::sorts::insertionsort::insertion_sort;
+use crate::sorts::{
+    auto_thread_count,
+    error::SortError,
+    insertionsort::insertion_sort_by,
+    ordering_from_in_order,
+    pool::{default_pool, PoolScope, SortPool},
+    Capabilities, Sorter, ThreadCountReport,
+};
 
 /// Performs a quicksort on a list of elements.
 ///
 /// # Params
 /// - `list` - The `Vec<T>` to sort.
 /// - `in_order` - The closure to use to sort the array. Determines if its `first`
-/// argument comes before its `second` argument.
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
 ///
 /// # Returns
 /// - The sorted `Vec<T>`.
 ///
 /// # Notes
-/// - Avoid using `<=` or `>=`, as this sort doesn't work properly with those comparisons.
-/// - Stick to using `<` and `>` for comparing elements.
+/// The `Vec` is moved into the sort, so its elements are dropped if `in_order` panics.
+/// Use [`quick_sort_in_place`] on a borrowed slice to keep them.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort;
 ///
 /// // sort using a closure to sort elements in ascending order
-/// // NOTE: avoid using <= or >=, as this sort doesn't work properly with those comparisons.
 /// let mut list = vec![4, 5, 2, 1, 3];
 /// list = quick_sort(list, &Box::new(|num1: &i32, num2: &i32| -> bool { num1 < num2 }));
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn quick_sort<T, U>(list: Vec<T>, in_order: &Box<U>) -> Vec<T>
+pub fn quick_sort<T, U>(mut list: Vec<T>, in_order: &U) -> Vec<T>
 where
-    T: Clone,              // we want to be able to clone the datatype held in the vector
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
 {
-    quick_sort_recursive(list, in_order)
+    quick_sort_in_place(&mut list, in_order);
+
+    list
 }
 
-/// Performs a recursive quicksort. Helper function for quick_sort.
+/// Performs a quicksort on a borrowed list of elements, sorting it in place.
 ///
 /// # Params
-/// - `list` - The `Vec<T>` to sort.
+/// - `list` - The slice to sort.
 /// - `in_order` - The closure to use to sort the array. Determines if its `first`
-/// argument comes before its `second` argument.
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Notes
+/// If `in_order` panics, the panic is passed on to the caller, but the slice still holds
+/// every one of its elements, in some order.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort_in_place;
+///
+/// // sort using a closure to sort elements in ascending order
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// quick_sort_in_place(&mut list, |num1: &i32, num2: &i32| -> bool { num1 < num2 });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn quick_sort_in_place<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    quick_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a quicksort on a list of elements, using a closure that returns an `Ordering`.
+/// The list is sorted in place, without any allocations or clones. The sort is not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// quick_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn quick_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    quick_sort_recursive(list, &compare);
+}
+
+/// Performs a quicksort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn quick_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    quick_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a quicksort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn quick_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    quick_sort_by(list, T::cmp);
+}
+
+/// Performs a quicksort with three-way partitioning on a list of elements.
+///
+/// Each partition splits the list into three parts instead of two: the elements that
+/// come before the pivot, the elements equal to it, and the elements that come after it
+/// (Dijkstra's "Dutch national flag" partitioning). The equal elements are already in
+/// place, so they are never looked at again. This makes lists with only a few distinct
+/// keys (status codes, enum tags, ...) much faster to sort, since each distinct key only
+/// takes one partition to finish off.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort_three_way;
+///
+/// // create a list of elements
+/// let mut list = vec![2, 1, 2, 0, 1, 2, 0];
+/// // sort using a closure to sort elements in ascending order
+/// quick_sort_three_way(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![0, 0, 1, 1, 2, 2, 2], list);
+///
+/// ```
+pub fn quick_sort_three_way<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    quick_sort_three_way_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a quicksort with three-way partitioning on a list of elements, using a
+/// closure that returns an `Ordering`. The list is sorted in place, without any
+/// allocations or clones. The sort is not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort_three_way_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// quick_sort_three_way_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn quick_sort_three_way_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    quick_sort_three_way_recursive(list, &compare);
+}
+
+/// Performs a quicksort with three-way partitioning on a list of elements, ordering them
+/// by the key that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn quick_sort_three_way_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    quick_sort_three_way_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a quicksort with three-way partitioning on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn quick_sort_three_way_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    quick_sort_three_way_by(list, T::cmp);
+}
+
+/// Performs a dual-pivot quicksort on a list of elements.
+///
+/// Instead of one pivot, two pivots are picked from a sorted sample of five elements, and
+/// each partition splits the list into three parts: the elements that come before the
+/// first pivot, the ones between the two pivots, and the ones that come after the second
+/// pivot (Yaroslavskiy's partitioning). Short partitions are insertion sorted, using the
+/// same cutoff as [`quick_sort`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::dual_pivot_quick_sort;
+///
+/// // create a list of elements
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort using a closure to sort elements in ascending order
+/// dual_pivot_quick_sort(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn dual_pivot_quick_sort<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    dual_pivot_quick_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a dual-pivot quicksort on a list of elements, using a closure that returns an
+/// `Ordering`. The list is sorted in place, without any allocations or clones. The sort is
+/// not stable.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::dual_pivot_quick_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// dual_pivot_quick_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn dual_pivot_quick_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    dual_pivot_quick_sort_recursive(list, &compare);
+}
+
+/// Performs a dual-pivot quicksort on a list of elements, ordering them by the key that
+/// the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn dual_pivot_quick_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    dual_pivot_quick_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a dual-pivot quicksort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn dual_pivot_quick_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    dual_pivot_quick_sort_by(list, T::cmp);
+}
+
+// partitions smaller than this are finished off with an insertion sort
+pub(crate) static CUTOFF: usize = 100;
+
+// partitions smaller than this aren't worth handing to another thread
+static PARALLEL_CUTOFF: usize = 4096;
+
+/// Performs a multithreaded quicksort on a list of elements.
+///
+/// The list is partitioned using the same median-of-three logic as [`quick_sort`]. Every
+/// time a partition is split, the smaller side is handed to the pool as a new job, while
+/// the current thread keeps going with the larger side. The new job waits on the current
+/// worker's own queue, where an idle worker can steal it.
+/// Partitions below a size threshold are sorted sequentially, and so is the whole list if
+/// it is below that threshold.
+///
+/// The sort runs on the [`default_pool`], which every call shares, with at most
+/// `num_threads` of its jobs running at once. Use [`quick_sort_pooled`] to run it on a
+/// pool of your own instead.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+/// - `num_threads` - The most threads to sort with at once.
 ///
 /// # Returns
-/// - The sorted `Vec<T>`.
+/// - `Ok(())` once the list is sorted, [`SortError::InvalidThreadCount`] if `num_threads`
+///   is zero, or [`SortError::WorkerPanicked`] if a thread panicked.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::quicksort::quick_sort_parallel;
+///
+/// let mut list: Vec<i32> = (0..100_000).rev().collect();
+/// quick_sort_parallel(&mut list, &|num1: &i32, num2: &i32| -> bool { num1 < num2 }, 4)
+///     .expect("Error when doing multithreaded quicksort");
+/// assert_eq!((0..100_000).collect::<Vec<i32>>(), list);
+///
+/// ```
+pub fn quick_sort_parallel<T, U>(
+    list: &mut [T],
+    in_order: &U,
+    num_threads: u32,
+) -> Result<(), SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    quick_sort_parallel_by(
+        list,
+        |first, second| ordering_from_in_order(in_order, first, second),
+        num_threads,
+    )
+}
+
+/// Performs a multithreaded quicksort on a list of elements, using a closure that returns
+/// an `Ordering`. See [`quick_sort_parallel`] for how the work is split up.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `num_threads` - The most threads to sort with at once.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, [`SortError::InvalidThreadCount`] if `num_threads`
+///   is zero, or [`SortError::WorkerPanicked`] if a thread panicked.
+pub fn quick_sort_parallel_by<T, F>(
+    list: &mut [T],
+    compare: F,
+    num_threads: u32,
+) -> Result<(), SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    if num_threads == 0 {
+        return Err(SortError::InvalidThreadCount);
+    }
+    if num_threads == 1 || list.len() < PARALLEL_CUTOFF {
+        return SortError::catch_panic(|| quick_sort_recursive(list, &compare));
+    }
+
+    quick_sort_on_pool(list, compare, default_pool()?, num_threads as usize)
+}
+
+/// Performs a multithreaded quicksort on a list of elements, picking the number of
+/// threads with [`auto_thread_count`]. Short lists are sorted on the calling thread.
+/// Like [`quick_sort_parallel`], the sort runs on the [`default_pool`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Returns
+/// - The thread configuration that was used once the list is sorted, or
+///   [`SortError::WorkerPanicked`] if a thread panicked.
+pub fn quick_sort_parallel_auto<T, U>(
+    list: &mut [T],
+    in_order: &U,
+) -> Result<ThreadCountReport, SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    quick_sort_parallel_auto_by(list, |first, second| {
+        ordering_from_in_order(in_order, first, second)
+    })
+}
+
+/// Performs a multithreaded quicksort on a list of elements, picking the number of
+/// threads with [`auto_thread_count`] and using a closure that returns an `Ordering`.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The thread configuration that was used once the list is sorted, or
+///   [`SortError::WorkerPanicked`] if a thread panicked.
+pub fn quick_sort_parallel_auto_by<T, F>(
+    list: &mut [T],
+    compare: F,
+) -> Result<ThreadCountReport, SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    let report = auto_thread_count(list.len());
+    quick_sort_parallel_by(list, compare, report.num_threads)?;
+
+    Ok(report)
+}
+
+/// Performs a multithreaded quicksort on a list of elements, running the work on the
+/// workers of a [`SortPool`] of your own instead of the [`default_pool`]. The work is split
+/// up the same way as in [`quick_sort_parallel`].
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+/// - `pool` - The pool to run the sort on.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+pub fn quick_sort_pooled<T, U>(
+    list: &mut [T],
+    in_order: &U,
+    pool: &SortPool,
+) -> Result<(), SortError>
+where
+    T: Send,
+    U: Fn(&T, &T) -> bool + Sync, // we want a closure to compare the two values and return a bool
+{
+    quick_sort_pooled_by(
+        list,
+        |first, second| ordering_from_in_order(in_order, first, second),
+        pool,
+    )
+}
+
+/// Performs a multithreaded quicksort on a list of elements on the workers of a
+/// [`SortPool`], using a closure that returns an `Ordering`.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `pool` - The pool to run the sort on.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+pub fn quick_sort_pooled_by<T, F>(
+    list: &mut [T],
+    compare: F,
+    pool: &SortPool,
+) -> Result<(), SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    if pool.num_threads() == 1 || list.len() < PARALLEL_CUTOFF {
+        return SortError::catch_panic(|| quick_sort_recursive(list, &compare));
+    }
+
+    // the pool's own size is the only limit on how many jobs run at once
+    quick_sort_on_pool(list, compare, pool, usize::MAX)
+}
+
+/// Runs a multithreaded quicksort on the workers of a pool. Helper function for
+/// quick_sort_parallel and quick_sort_pooled.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `pool` - The pool to run the sort on.
+/// - `max_jobs` - The most jobs of this sort that can be running or queued at once.
+///
+/// # Returns
+/// - `Ok(())` once the list is sorted, or [`SortError::WorkerPanicked`] if a job panicked.
+fn quick_sort_on_pool<T, F>(
+    list: &mut [T],
+    compare: F,
+    pool: &SortPool,
+    max_jobs: usize,
+) -> Result<(), SortError>
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
+{
+    if list.len() < PARALLEL_CUTOFF {
+        return SortError::catch_panic(|| quick_sort_recursive(list, &compare));
+    }
+
+    // the whole list is a job too, so that a panic while partitioning it is reported
+    let free_jobs = AtomicUsize::new(max_jobs - 1);
+    let compare = &compare;
+    let free_jobs = &free_jobs;
+    pool.scope(|scope| scope.spawn(move || quick_sort_job(scope, list, compare, free_jobs)))
+}
+
+/// Partitions the list until what is left is short enough to sort sequentially, handing
+/// the smaller side of every partition to the pool as a new job. The job goes on this
+/// worker's own queue, so it is picked up by this worker once it is done with the larger
+/// side, unless an idle worker steals it first. Helper function for quick_sort_pooled.
+///
+/// # Params
+/// - `scope` - The scope to spawn the jobs for the smaller sides on.
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+/// - `free_jobs` - How many more jobs the sort can spawn. When it runs out, the smaller
+///   side is sorted by this job instead.
 ///
 /// # Notes
-/// - Avoid using `<=` or `>=`, as this sort doesn't work properly with those comparisons.
-/// - Stick to using `<` and `>` for comparing elements.
-/// - This implementation could be made better, if I knew how to share multiple mutable references
-/// to modify the list rather than copying parts of it to subsequent recursive calls (Is this possible in unsafe Rust?).
-/// This means that this algorithm could likely be made better in terms of memory management. It's still fast, though.
-fn quick_sort_recursive<T, U>(mut list: Vec<T>, in_order: &Box<U>) -> Vec<T>
-where
-    T: Clone,              // we want to be able to clone the datatype held in the vector
-    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+/// - A job never waits on another one, so a pool thread is only ever busy while there is
+///   sorting to do, and other sorts sharing the pool can run their jobs in between.
+fn quick_sort_job<'scope, T, F>(
+    scope: &'scope PoolScope<'scope, '_>,
+    mut list: &'scope mut [T],
+    compare: &'scope F,
+    free_jobs: &'scope AtomicUsize,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync, // we want a closure to compare the two values and return an ordering
 {
-    static CUTOFF: usize = 100;
+    while list.len() >= PARALLEL_CUTOFF {
+        let pivot_idx = partition(list, compare);
+
+        // hand the smaller side to the pool if another job is allowed, and keep going with
+        // the larger one
+        let (left_list, right_list) = list.split_at_mut(pivot_idx);
+        let right_list = &mut right_list[1..];
+        let (smaller_list, larger_list) = if left_list.len() < right_list.len() {
+            (left_list, right_list)
+        } else {
+            (right_list, left_list)
+        };
+        let job_is_free = free_jobs
+            .fetch_update(AtomicOrdering::SeqCst, AtomicOrdering::SeqCst, |free| {
+                free.checked_sub(1)
+            })
+            .is_ok();
+        if job_is_free {
+            scope.spawn(move || quick_sort_job(scope, smaller_list, compare, free_jobs));
+        } else {
+            quick_sort_recursive(smaller_list, compare);
+        }
 
-    // return the list if it is empty
-    if list.is_empty() {
-        return list;
+        list = larger_list;
     }
 
-    let (left, right) = (0, list.len() - 1);
+    quick_sort_recursive(list, compare);
+    // this job is done, so another one can take its place
+    free_jobs.fetch_add(1, AtomicOrdering::SeqCst);
+}
 
-    if right - left + 1 >= CUTOFF {
-        // if we aren't at or below the cutoff
+/// Performs a recursive quicksort in place. Helper function for quick_sort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Notes
+/// - The two partitions are separate mutable slices obtained with `split_at_mut`, so
+///   nothing is copied between recursive calls.
+/// - Only the smaller partition is recursed into, while the larger one is handled by the
+///   loop, which keeps the recursion depth at `O(log n)`.
+fn quick_sort_recursive<T, F>(mut list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // keep going while we are above the cutoff
+    while list.len() >= CUTOFF {
+        let pivot_idx = partition(list, compare);
 
-        if right - left > 0 {
-            // we have at least two items in this partition
+        // the pivot is now in place, so we can sort the two partitions around it
+        let (left_list, right_list) = list.split_at_mut(pivot_idx);
+        let right_list = &mut right_list[1..];
+        if left_list.len() < right_list.len() {
+            quick_sort_recursive(left_list, compare);
+            list = right_list;
+        } else {
+            quick_sort_recursive(right_list, compare);
+            list = left_list;
+        }
+    }
 
-            // get the pivot from a median of three
-            median_of_three(&mut list, &in_order, left, right);
+    insertion_sort_by(list, compare);
+}
 
-            let pivot_idx = right - 1;
-            let pivot = list[pivot_idx].clone();
-            // we don't want to compare list[j] with the pivot itself
-            let (mut i, mut j) = (left, pivot_idx - 1);
+/// Performs a recursive quicksort with three-way partitioning in place. Helper function
+/// for quick_sort_three_way.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Notes
+/// - Like [`quick_sort_recursive`], only the smaller partition is recursed into. The
+///   elements equal to the pivot are skipped entirely.
+fn quick_sort_three_way_recursive<T, F>(mut list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // keep going while we are above the cutoff
+    while list.len() >= CUTOFF {
+        let (less_end, greater_start) = partition_three_way(list, compare);
+
+        // everything in between is equal to the pivot, so it is already in place
+        let (left_list, right_list) = list.split_at_mut(less_end);
+        let right_list = &mut right_list[greater_start - less_end..];
+        if left_list.len() < right_list.len() {
+            quick_sort_three_way_recursive(left_list, compare);
+            list = right_list;
+        } else {
+            quick_sort_three_way_recursive(right_list, compare);
+            list = left_list;
+        }
+    }
 
-            loop {
-                // increment i (we are looking for an element larger than the pivot if sorting in ascending order)
-                while in_order(&list[i], &pivot) {
-                    i += 1;
-                }
+    insertion_sort_by(list, compare);
+}
 
-                // decrement j (we are looking for an element that is smaller than the pivot if we are sorting in ascending order)
-                while j > 0 && !in_order(&list[j], &pivot) {
-                    j -= 1;
-                }
+/// Partitions the list into the elements that come before a pivot, the elements equal
+/// to it and the elements that come after it. The pivot is chosen with a median of three.
+///
+/// # Params
+/// - `list` - The slice to partition. Must have at least three elements.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - `(less_end, greater_start)`, where `list[..less_end]` come before the pivot,
+///   `list[less_end..greater_start]` are equal to it and `list[greater_start..]` come
+///   after it. The middle part always holds at least the pivot.
+fn partition_three_way<T, F>(list: &mut [T], compare: &F) -> (usize, usize)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let right = list.len() - 1;
 
-                // if i and j have crossed
-                if i >= j {
-                    break;
-                }
+    // get the pivot from a median of three, and move it to the front
+    median_of_three(list, compare, 0, right);
+    list.swap(0, right - 1);
 
-                // swap list[i] and list[j] otherwise
-                let (left_list, right_list) = list.split_at_mut(j);
-                swap(&mut left_list[i], &mut right_list[0]);
+    // list[..less_end] come before the pivot, list[less_end..i] are equal to it,
+    // list[i..greater_start] haven't been looked at yet and list[greater_start..] come after it
+    let (mut less_end, mut i, mut greater_start) = (0, 1, list.len());
+    while i < greater_start {
+        // list[less_end] is always equal to the pivot, so it can stand in for it
+        match compare(&list[i], &list[less_end]) {
+            Ordering::Less => {
+                list.swap(less_end, i);
+                less_end += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                greater_start -= 1;
+                list.swap(i, greater_start);
+            }
+            Ordering::Equal => {
+                i += 1;
             }
+        }
+    }
 
-            // since i and j have crossed, we swap list[i] and the pivot
-            list[right - 1] = list[i].clone();
-            list[i] = pivot.clone();
+    (less_end, greater_start)
+}
+
+/// Performs a recursive dual-pivot quicksort in place. Helper function for
+/// dual_pivot_quick_sort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Notes
+/// - The two smaller of the three partitions are recursed into, while the largest one
+///   is handled by the loop, which keeps the recursion depth at `O(log n)`.
+fn dual_pivot_quick_sort_recursive<T, F>(mut list: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    // keep going while we are above the cutoff
+    while list.len() >= CUTOFF {
+        let (first_pivot_idx, second_pivot_idx, pivots_equal) = partition_dual_pivot(list, compare);
+
+        // both pivots are now in place, so they aren't part of any partition
+        let (left_list, rest) = list.split_at_mut(first_pivot_idx);
+        let (middle_list, right_list) =
+            rest[1..].split_at_mut(second_pivot_idx - first_pivot_idx - 1);
+        let right_list = &mut right_list[1..];
+        // if the pivots are equal, everything between them is too
+        let middle_list = if pivots_equal {
+            &mut middle_list[..0]
+        } else {
+            middle_list
+        };
+
+        let mut partitions = [left_list, middle_list, right_list];
+        let largest = if partitions[0].len() > partitions[1].len() {
+            0
+        } else {
+            1
+        };
+        let largest = if partitions[largest].len() > partitions[2].len() {
+            largest
+        } else {
+            2
+        };
+        partitions.swap(largest, 2);
+
+        let [first_list, second_list, largest_list] = partitions;
+        dual_pivot_quick_sort_recursive(first_list, compare);
+        dual_pivot_quick_sort_recursive(second_list, compare);
+        list = largest_list;
+    }
+
+    insertion_sort_by(list, compare);
+}
+
+/// Partitions the list around two pivots, chosen as the second and fourth elements of a
+/// sorted sample of five.
+///
+/// # Params
+/// - `list` - The slice to partition. Must have at least eight elements.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - `(first_pivot_idx, second_pivot_idx, pivots_equal)`. Every element before the first
+///   pivot comes before it, every element after the second pivot comes after it, and
+///   every element in between is somewhere between the two. `pivots_equal` is whether
+///   the two pivots are equal, in which case everything in between is equal to them.
+fn partition_dual_pivot<T, F>(list: &mut [T], compare: &F) -> (usize, usize, bool)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let len = list.len();
 
-            // the pivot is now in place at i
-            // we can now recursively call quicksort on the two partitions
-            let (left_list, right_list) = (list[left..i].to_vec(), list[i + 1..=right].to_vec());
-            // modifying the list to make the borrow checker happy
-            list = quick_sort_recursive(left_list, in_order);
-            list.append(&mut vec![pivot]);
-            list.append(&mut quick_sort_recursive(right_list, in_order));
+    // pick five elements spread evenly around the middle, and sort them in place
+    let seventh = (len >> 3) + (len >> 6) + 1;
+    let center = len / 2;
+    let samples = [
+        center - 2 * seventh,
+        center - seventh,
+        center,
+        center + seventh,
+        center + 2 * seventh,
+    ];
+    for i in 1..samples.len() {
+        let mut j = i;
+        while j > 0 && compare(&list[samples[j]], &list[samples[j - 1]]) == Ordering::Less {
+            list.swap(samples[j], samples[j - 1]);
+            j -= 1;
         }
-    } else {
-        insertion_sort(&mut list, in_order);
     }
 
-    // return modified list
-    list
+    // the pivots sit at the two ends while partitioning
+    list.swap(0, samples[1]);
+    list.swap(len - 1, samples[3]);
+    let pivots_equal = compare(&list[0], &list[len - 1]) == Ordering::Equal;
+
+    // list[1..less_end] come before the first pivot, list[less_end..i] are between the
+    // pivots, list[i..=greater_start] haven't been looked at yet and
+    // list[greater_start + 1..len - 1] come after the second pivot
+    let (mut less_end, mut i, mut greater_start) = (1, 1, len - 2);
+    while i <= greater_start {
+        if compare(&list[i], &list[0]) == Ordering::Less {
+            list.swap(i, less_end);
+            less_end += 1;
+        } else if compare(&list[i], &list[len - 1]) == Ordering::Greater {
+            // find an element from the right that can be swapped over
+            while i < greater_start
+                && compare(&list[greater_start], &list[len - 1]) == Ordering::Greater
+            {
+                greater_start -= 1;
+            }
+            if i == greater_start {
+                // there isn't one, so this was the last element left to look at
+                greater_start -= 1;
+                break;
+            }
+            list.swap(i, greater_start);
+            greater_start -= 1;
+
+            // the element swapped over may belong with the small ones
+            if compare(&list[i], &list[0]) == Ordering::Less {
+                list.swap(i, less_end);
+                less_end += 1;
+            }
+        }
+        i += 1;
+    }
+
+    // move the pivots into place, next to their partitions
+    let first_pivot_idx = less_end - 1;
+    let second_pivot_idx = greater_start + 1;
+    list.swap(0, first_pivot_idx);
+    list.swap(len - 1, second_pivot_idx);
+
+    (first_pivot_idx, second_pivot_idx, pivots_equal)
 }
 
-/// Performs a median-of-three ordering of elements in the `Vec` for sorting with
+/// Partitions the list around a pivot chosen with a median of three.
+///
+/// # Params
+/// - `list` - The slice to partition. Must have at least three elements.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The index that the pivot ended up at. Every element before it does not come after
+///   the pivot, and every element after it does not come before the pivot.
+pub(crate) fn partition<T, F>(list: &mut [T], compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let right = list.len() - 1;
+
+    // get the pivot from a median of three
+    median_of_three(list, compare, 0, right);
+
+    // the pivot stays at this index until the end, since i and j never reach it
+    let pivot_idx = right - 1;
+    // we don't want to compare list[j] with the pivot itself
+    let (mut i, mut j) = (0, pivot_idx - 1);
+
+    loop {
+        // increment i (we are looking for an element larger than the pivot if sorting in ascending order)
+        // the pivot can't come before itself, but an inconsistent comparator might say it
+        // does, so i is stopped there instead of relying on that
+        while i < pivot_idx && compare(&list[i], &list[pivot_idx]) == Ordering::Less {
+            i += 1;
+        }
+
+        // decrement j (we are looking for an element that is smaller than the pivot if we are sorting in ascending order)
+        // both i and j stop on elements equal to the pivot, so lots of duplicates still
+        // get split evenly between the two partitions
+        while j > 0 && compare(&list[j], &list[pivot_idx]) == Ordering::Greater {
+            j -= 1;
+        }
+
+        // if i and j have crossed
+        if i >= j {
+            break;
+        }
+
+        // swap list[i] and list[j] otherwise
+        let (left_list, right_list) = list.split_at_mut(j);
+        swap(&mut left_list[i], &mut right_list[0]);
+
+        // move past the swapped elements, as they may both be equal to the pivot
+        i += 1;
+        j -= 1;
+    }
+
+    // since i and j have crossed, we swap list[i] and the pivot
+    if i != pivot_idx {
+        let (left_list, right_list) = list.split_at_mut(pivot_idx);
+        swap(&mut left_list[i], &mut right_list[0]);
+    }
+
+    i
+}
+
+/// Performs a median-of-three ordering of elements in the slice for sorting with
 /// quicksort.
 ///
 /// Sorts the leftmost, center, and rightmost elements, then swaps the center
-/// element with the second-to-last element in the `Vec`.
+/// element with the second-to-last element in the slice.
 ///
 /// # Params
 /// - `list` - The list to perform the ordering on.
-/// - `in_order` - The boxed closure to do the ordering based upon.
+/// - `compare` - The closure to do the ordering based upon.
 /// - `left` - The first (inclusive) index to do the ordering upon.
 /// - `right` - The last (inclusive) index to do the ordering upon.
-fn median_of_three<T, U>(list: &mut Vec<T>, in_order: &Box<U>, left: usize, right: usize)
+fn median_of_three<T, F>(list: &mut [T], compare: &F, left: usize, right: usize)
 where
-    T: Clone,              // we want to be able to clone the datatype held in the vector
-    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     let center = ((right - left) / 2) + left;
 
     // compare the first and center elements
-    if !in_order(&list[left], &list[center]) {
+    if compare(&list[left], &list[center]) == Ordering::Greater {
         let (first, second) = list.split_at_mut(center);
         swap(&mut first[left], &mut second[0]);
     }
 
     // compare the first and last elements
-    if !in_order(&list[left], &list[right]) {
+    if compare(&list[left], &list[right]) == Ordering::Greater {
         let (first, second) = list.split_at_mut(right);
         swap(&mut first[left], &mut second[0]);
     }
 
     // compare the middle and last elements
-    if !in_order(&list[center], &list[right]) {
+    if compare(&list[center], &list[right]) == Ordering::Greater {
         let (first, second) = list.split_at_mut(right);
         swap(&mut first[center], &mut second[0]);
     }
@@ -160,3 +943,139 @@ where
     let (first, second) = list.split_at_mut(right - 1);
     swap(&mut first[center], &mut second[0]);
 }
+
+/// [`Sorter`] for [`quick_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuickSort;
+
+impl<T> Sorter<T> for QuickSort {
+    fn name(&self) -> &'static str {
+        "Quicksort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        quick_sort_by(list, compare);
+    }
+}
+
+/// [`Sorter`] for [`quick_sort_three_way`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuickSortThreeWay;
+
+impl<T> Sorter<T> for QuickSortThreeWay {
+    fn name(&self) -> &'static str {
+        "Three-way quicksort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        quick_sort_three_way_by(list, compare);
+    }
+}
+
+/// [`Sorter`] for [`dual_pivot_quick_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DualPivotQuickSort;
+
+impl<T> Sorter<T> for DualPivotQuickSort {
+    fn name(&self) -> &'static str {
+        "Dual-pivot quicksort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        dual_pivot_quick_sort_by(list, compare);
+    }
+}
+
+/// [`Sorter`] for [`quick_sort_parallel`].
+#[derive(Debug, Clone, Copy)]
+pub struct QuickSortParallel {
+    /// The number of worker threads to sort with. Must not be zero.
+    pub num_threads: u32,
+}
+
+impl<T: Send> Sorter<T> for QuickSortParallel {
+    fn name(&self) -> &'static str {
+        "Quicksort (multithreaded)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: true,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        if let Err(err_val) = self.try_sort_by(list, compare) {
+            panic!("Error when doing multithreaded quicksort ({})", err_val);
+        }
+    }
+
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError> {
+        quick_sort_parallel_by(list, compare, self.num_threads)
+    }
+}
+
+/// [`Sorter`] for [`quick_sort_pooled`].
+#[derive(Debug, Clone, Copy)]
+pub struct QuickSortPooled<'a> {
+    /// The pool to run the sort on.
+    pub pool: &'a SortPool,
+}
+
+impl<T: Send> Sorter<T> for QuickSortPooled<'_> {
+    fn name(&self) -> &'static str {
+        "Quicksort (pooled)"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: true,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        if let Err(err_val) = self.try_sort_by(list, compare) {
+            panic!("Error when doing pooled quicksort ({})", err_val);
+        }
+    }
+
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError> {
+        quick_sort_pooled_by(list, compare, self.pool)
+    }
+}
@@ -0,0 +1,217 @@
+/// A key that can be sorted by [`radix_sort`], one byte at a time.
+///
+/// Implemented for every primitive integer and float. Signed integers have their sign bit
+/// flipped, so negative numbers come before positive ones, and floats are put in the IEEE
+/// total order (the same order as [`f64::total_cmp`]): `-NaN`, `-inf`, the negative
+/// numbers, `-0.0`, `0.0`, the positive numbers, `inf` and `NaN`.
+pub trait RadixKey: Copy {
+    /// The number of bytes in the key, which is how many passes a radix sort makes over it.
+    const BYTES: usize;
+
+    /// Gets one byte of the key, counting from the least significant byte. Comparing two
+    /// keys byte by byte, from the most significant byte down, has to give the same order
+    /// as comparing the keys themselves.
+    ///
+    /// # Params
+    /// - `byte` - Which byte to get. Always less than `BYTES`.
+    fn radix_byte(&self, byte: usize) -> u8;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($unsigned:ty),*) => {
+        $(
+            impl RadixKey for $unsigned {
+                const BYTES: usize = std::mem::size_of::<$unsigned>();
+
+                fn radix_byte(&self, byte: usize) -> u8 {
+                    (*self >> (8 * byte)) as u8
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($signed:ty => $unsigned:ty),*) => {
+        $(
+            impl RadixKey for $signed {
+                const BYTES: usize = std::mem::size_of::<$signed>();
+
+                fn radix_byte(&self, byte: usize) -> u8 {
+                    // flipping the sign bit moves the negative numbers below the positive ones
+                    let bits = (*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1));
+                    bits.radix_byte(byte)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_float {
+    ($($float:ty => $unsigned:ty),*) => {
+        $(
+            impl RadixKey for $float {
+                const BYTES: usize = std::mem::size_of::<$float>();
+
+                fn radix_byte(&self, byte: usize) -> u8 {
+                    // positive floats only need their sign bit set to come after the negative
+                    // ones, but negative floats get bigger as their bits get smaller, so all of
+                    // their bits are flipped instead
+                    let bits = self.to_bits();
+                    let sign_bit: $unsigned = 1 << (<$unsigned>::BITS - 1);
+                    let bits = if bits & sign_bit == 0 {
+                        bits | sign_bit
+                    } else {
+                        !bits
+                    };
+                    bits.radix_byte(byte)
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_radix_key_float!(f32 => u32, f64 => u64);
+
+/// Performs an LSD radix sort on a list of integers or floats, in ascending order.
+///
+/// Unlike every other sort in `sorts`, this never compares two elements. Instead, the list
+/// is counting sorted by each byte of the keys, starting with the least significant one.
+/// Every pass is stable, so after the last pass the list is in order, in `O(n * BYTES)`
+/// time. Passes where every key has the same byte (like the top bytes of small numbers)
+/// are skipped. A scratch buffer the size of the list is allocated.
+///
+/// # Params
+/// - `list` - The slice to sort.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::radixsort::radix_sort;
+///
+/// let mut list: Vec<i32> = vec![4, -5, 2, 1, -3];
+/// radix_sort(&mut list);
+/// assert_eq!(vec![-5, -3, 1, 2, 4], list);
+///
+/// let mut list = vec![0.5, -1.0, 2.5, -0.0, 0.0];
+/// radix_sort(&mut list);
+/// assert_eq!(vec![-1.0, -0.0, 0.0, 0.5, 2.5], list);
+///
+/// ```
+pub fn radix_sort<K>(list: &mut [K])
+where
+    K: RadixKey,
+{
+    lsd_radix_sort(list, |key| *key);
+}
+
+/// Performs an LSD radix sort on a list of elements, ordering them by the integer (or
+/// float) key that the `key` closure extracts from each element. The sort is stable.
+///
+/// The elements don't need to be `Clone`. The keys are sorted along with the index of
+/// the element they came from, and the elements are then moved into place with swaps,
+/// following each cycle of the resulting permutation.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by. It is called exactly
+///   once per element.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::radixsort::radix_sort_by_key;
+///
+/// let mut list = vec![("b", 2_u32), ("a", 1), ("c", 2), ("d", 0)];
+/// radix_sort_by_key(&mut list, |pair| pair.1);
+/// assert_eq!(vec![("d", 0), ("a", 1), ("b", 2), ("c", 2)], list);
+///
+/// ```
+pub fn radix_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: RadixKey,
+    F: Fn(&T) -> K,
+{
+    let mut keys: Vec<(K, usize)> = list
+        .iter()
+        .enumerate()
+        .map(|(idx, element)| (key(element), idx))
+        .collect();
+    lsd_radix_sort(&mut keys, |pair| pair.0);
+
+    // keys[idx].1 is now where the element that belongs at idx is, so follow each cycle
+    // of that, swapping every element into place
+    let mut sources: Vec<usize> = keys.into_iter().map(|(_, idx)| idx).collect();
+    for start in 0..list.len() {
+        let mut current = start;
+        loop {
+            let source = sources[current];
+            // mark this one as done, so the cycle isn't followed again
+            sources[current] = current;
+            if source == start || source == current {
+                break;
+            }
+            list.swap(current, source);
+            current = source;
+        }
+    }
+}
+
+/// Counting sorts the list by each byte of the keys, from the least significant one up.
+/// Helper function for radix_sort.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key of an element.
+fn lsd_radix_sort<T, K, F>(list: &mut [T], key: F)
+where
+    T: Copy,
+    K: RadixKey,
+    F: Fn(&T) -> K,
+{
+    let len = list.len();
+    if len < 2 {
+        return;
+    }
+
+    let mut buffer = list.to_vec();
+    // whether the latest pass left the elements in the buffer rather than the list
+    let mut in_buffer = false;
+    for byte in 0..K::BYTES {
+        let (from, to) = if in_buffer {
+            (&buffer[..], &mut list[..])
+        } else {
+            (&list[..], &mut buffer[..])
+        };
+
+        let mut counts = [0; 256];
+        for element in from.iter() {
+            counts[key(element).radix_byte(byte) as usize] += 1;
+        }
+        if counts.contains(&len) {
+            // every key has the same byte here, so this pass wouldn't move anything
+            continue;
+        }
+
+        // turn the counts into where each byte's elements start
+        let mut offsets = [0; 256];
+        let mut total = 0;
+        for (offset, count) in offsets.iter_mut().zip(counts) {
+            *offset = total;
+            total += count;
+        }
+
+        for element in from.iter() {
+            let offset = &mut offsets[key(element).radix_byte(byte) as usize];
+            to[*offset] = *element;
+            *offset += 1;
+        }
+        in_buffer = !in_buffer;
+    }
+
+    if in_buffer {
+        list.copy_from_slice(&buffer);
+    }
+}
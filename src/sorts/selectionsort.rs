@@ -1,14 +1,19 @@
-use std::mem::swap;
+use std::{cmp::Ordering, mem::swap};
+
+use crate::sorts::{ordering_from_in_order, Capabilities, Sorter};
 
 /// Performs a selection sort on a list of elements.
 ///
 /// # Params
 /// - `list` - The `Vec` to sort.
-/// - `comparison_closure` - The closure to use to sort the array.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
 ///
 /// # Example
 ///
-/// ```rust norun
+/// ```rust
+/// use sorting_algorithms::sorts::selectionsort::selection_sort;
 ///
 /// // create a list of elements
 /// let mut list = vec![4,5,2,1,3];
@@ -17,16 +22,45 @@ use std::mem::swap;
 /// assert_eq!(vec![1, 2, 3, 4, 5], list);
 ///
 /// ```
-pub fn selection_sort<T, U>(list: &mut Vec<T>, in_order: U)
+pub fn selection_sort<T, U>(list: &mut [T], in_order: U)
 where
     U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    selection_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a selection sort on a list of elements, using a closure that returns an `Ordering`.
+/// The sort is not stable, as swapping the smallest element into place can move an equal
+/// element behind another one.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::selectionsort::selection_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// selection_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn selection_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
 {
     // loop through the list
     for i in 0..list.len() {
         let mut element_to_add_idx = i;
         // find the value to add to the current position
         for j in (i + 1)..list.len() {
-            if !in_order(&list[element_to_add_idx], &list[j]) {
+            if compare(&list[element_to_add_idx], &list[j]) == Ordering::Greater {
                 element_to_add_idx = j;
             }
         }
@@ -39,3 +73,50 @@ where
         }
     }
 }
+
+/// Performs a selection sort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn selection_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    selection_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a selection sort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn selection_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    selection_sort_by(list, T::cmp);
+}
+
+/// [`Sorter`] for [`selection_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SelectionSort;
+
+impl<T> Sorter<T> for SelectionSort {
+    fn name(&self) -> &'static str {
+        "Selection sort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: false,
+            in_place: true,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        selection_sort_by(list, compare);
+    }
+}
@@ -0,0 +1,606 @@
+use std::{cmp::Ordering, ptr, slice};
+
+use crate::sorts::{
+    insertionsort::binary_insertion_sort_from, mergesort::MergeHole, ordering_from_in_order,
+    Capabilities, Sorter,
+};
+
+// lists shorter than this are sorted as one run, with a binary insertion sort
+static MIN_MERGE: usize = 64;
+
+// how many times in a row one run has to win during a merge before switching to galloping
+static MIN_GALLOP: usize = 7;
+
+/// Performs a timsort on a list of elements.
+///
+/// A timsort looks for runs that are already in order (or in reverse order) and merges
+/// them together, so it takes advantage of any order the list already has. A sorted or
+/// reversed list takes `n - 1` comparisons, and a list made of a few sorted pieces only
+/// needs those pieces merged. Short runs are made longer with a binary insertion sort
+/// first, and merges switch to galloping (skipping ahead with an exponential search) when
+/// one run keeps winning, which lets them move whole chunks of a run at once.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `in_order` - The closure to use to sort the array. Determines if its `first`
+///   argument comes before its `second` argument. Both strict (`<`) and non-strict
+///   (`<=`) comparisons work.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::timsort::tim_sort;
+///
+/// // create a list of elements
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort using a closure to sort elements in ascending order
+/// tim_sort(&mut list, |first, second| -> bool { first <= second });
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// ```
+pub fn tim_sort<T, U>(list: &mut [T], in_order: U)
+where
+    U: Fn(&T, &T) -> bool, // we want a closure to compare the two values and return a bool
+{
+    tim_sort_by(list, |first, second| {
+        ordering_from_in_order(&in_order, first, second)
+    });
+}
+
+/// Performs a timsort on a list of elements, using a closure that returns an `Ordering`.
+/// The sort is stable: runs are only reversed when they are strictly descending, and
+/// merges always take from the left run on a tie. Nothing is cloned, but a scratch buffer
+/// of up to half the list's length is allocated for the merges.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::timsort::tim_sort_by;
+///
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// // sort in descending order
+/// tim_sort_by(&mut list, |first, second| second.cmp(first));
+/// assert_eq!(vec![5, 4, 3, 2, 1], list);
+///
+/// ```
+pub fn tim_sort_by<T, F>(list: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let len = list.len();
+    if len < 2 {
+        return;
+    }
+
+    let min_run = min_run_length(len);
+    let mut state = MergeState {
+        runs: vec![],
+        buffer: Vec::with_capacity(len / 2),
+        min_gallop: MIN_GALLOP,
+    };
+
+    let mut start = 0;
+    while start < len {
+        let mut run_len = count_run(&mut list[start..], &compare);
+        if run_len < min_run {
+            // make the run longer, so that there aren't too many tiny merges
+            let forced_len = usize::min(min_run, len - start);
+            binary_insertion_sort_from(&mut list[start..start + forced_len], run_len, &compare);
+            run_len = forced_len;
+        }
+
+        state.runs.push(Run {
+            start,
+            len: run_len,
+        });
+        start += run_len;
+        state.merge_collapse(list, &compare);
+    }
+
+    state.merge_force_collapse(list, &compare);
+}
+
+/// Performs a timsort on a list of elements, ordering them by the key
+/// that the `key` closure extracts from each element.
+///
+/// # Params
+/// - `list` - The slice to sort.
+/// - `key` - The closure that returns the key to sort an element by.
+pub fn tim_sort_by_key<T, K, F>(list: &mut [T], key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    tim_sort_by(list, |first, second| key(first).cmp(&key(second)));
+}
+
+/// Performs a timsort on a list of elements, in ascending order.
+///
+/// # Params
+/// - `list` - The slice to sort.
+pub fn tim_sort_ord<T>(list: &mut [T])
+where
+    T: Ord,
+{
+    tim_sort_by(list, T::cmp);
+}
+
+/// Picks the shortest length a run is allowed to be, for a list of length `len`.
+///
+/// Lists shorter than `MIN_MERGE` are one run. Otherwise the result is between
+/// `MIN_MERGE / 2` and `MIN_MERGE`, picked so that `len / min_run` is a power of two or
+/// just under one, which keeps the merges at the end balanced.
+fn min_run_length(mut len: usize) -> usize {
+    // set if any of the bits shifted off are set
+    let mut remainder = 0;
+    while len >= MIN_MERGE {
+        remainder |= len & 1;
+        len >>= 1;
+    }
+
+    len + remainder
+}
+
+/// Finds the length of the run at the start of the list, reversing it if it is descending.
+///
+/// # Params
+/// - `list` - The slice to find the run in.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - The length of the run, which is now in ascending order.
+///
+/// # Notes
+/// - Descending runs have to be strictly descending, since reversing equal elements
+///   would swap their order and break the stability.
+fn count_run<T, F>(list: &mut [T], compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let len = list.len();
+    if len < 2 {
+        return len;
+    }
+
+    let mut run_len = 2;
+    if compare(&list[1], &list[0]) == Ordering::Less {
+        while run_len < len && compare(&list[run_len], &list[run_len - 1]) == Ordering::Less {
+            run_len += 1;
+        }
+        list[..run_len].reverse();
+    } else {
+        while run_len < len && compare(&list[run_len], &list[run_len - 1]) != Ordering::Less {
+            run_len += 1;
+        }
+    }
+
+    run_len
+}
+
+/// Counts how many elements at the start of a sorted list come before some element, by
+/// looking at indices 0, 1, 3, 7, ... until it finds one that doesn't, then doing a
+/// binary search in the last gap. This takes `O(log k)` comparisons when the answer is
+/// `k`, instead of the `O(log n)` of a plain binary search.
+///
+/// # Params
+/// - `list` - The sorted slice to search.
+/// - `from_back` - Whether to start from the end of the list instead, for when the answer
+///   is expected to be close to the end.
+/// - `is_before` - The closure that returns whether an element comes before the one being
+///   searched for. Has to return `true` for some elements at the start of the list and
+///   `false` for the rest.
+///
+/// # Returns
+/// - The number of elements that `is_before` returned `true` for.
+fn gallop<T, P>(list: &[T], from_back: bool, is_before: P) -> usize
+where
+    P: Fn(&T) -> bool,
+{
+    // everything before `low` is before the element, and everything from `high` on isn't
+    let mut low = 0;
+    let mut high = list.len();
+
+    let mut step = 1;
+    while step <= high - low {
+        if from_back {
+            let idx = high - step;
+            if is_before(&list[idx]) {
+                low = idx + 1;
+                break;
+            }
+            high = idx;
+        } else {
+            let idx = low + step - 1;
+            if !is_before(&list[idx]) {
+                high = idx;
+                break;
+            }
+            low = idx + 1;
+        }
+        step *= 2;
+    }
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if is_before(&list[mid]) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
+
+/// A run on the run stack, which is already sorted.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+/// The state kept between merges. Helper struct for tim_sort.
+struct MergeState<T> {
+    /// The runs that haven't been merged yet, from left to right.
+    runs: Vec<Run>,
+    /// The scratch buffer for the merges, which never takes ownership of the elements.
+    buffer: Vec<T>,
+    /// How many times in a row one run has to win before a merge starts galloping. Goes
+    /// down when galloping pays off, and up when it doesn't.
+    min_gallop: usize,
+}
+
+impl<T> MergeState<T> {
+    /// Merges runs on the stack until the run lengths shrink fast enough from the bottom
+    /// of the stack to the top, so that every run is longer than the two above it put
+    /// together, and longer than the one above it. This keeps the merges balanced, and
+    /// keeps the stack `O(log n)` runs deep.
+    ///
+    /// # Params
+    /// - `list` - The slice that the runs are in.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    ///
+    /// # Notes
+    /// - The run below the top three is checked too, as the invariants can otherwise break
+    ///   further down the stack after a merge.
+    fn merge_collapse<F>(&mut self, list: &mut [T], compare: &F)
+    where
+        F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+    {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            let runs = &self.runs;
+            if (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+                || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len)
+            {
+                // merge the middle run with whichever neighbour is shorter
+                if runs[n - 3].len < runs[n - 1].len {
+                    self.merge_at(list, n - 3, compare);
+                } else {
+                    self.merge_at(list, n - 2, compare);
+                }
+            } else if runs[n - 2].len <= runs[n - 1].len {
+                self.merge_at(list, n - 2, compare);
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Merges every run left on the stack, once the whole list has been split into runs.
+    ///
+    /// # Params
+    /// - `list` - The slice that the runs are in.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    fn merge_force_collapse<F>(&mut self, list: &mut [T], compare: &F)
+    where
+        F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+    {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            if n >= 3 && self.runs[n - 3].len < self.runs[n - 1].len {
+                self.merge_at(list, n - 3, compare);
+            } else {
+                self.merge_at(list, n - 2, compare);
+            }
+        }
+    }
+
+    /// Merges the run at `idx` on the stack with the one after it.
+    ///
+    /// # Params
+    /// - `list` - The slice that the runs are in.
+    /// - `idx` - The index on the stack of the left run.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    fn merge_at<F>(&mut self, list: &mut [T], idx: usize, compare: &F)
+    where
+        F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+    {
+        let left = self.runs[idx];
+        let right = self.runs.remove(idx + 1);
+        self.runs[idx].len += right.len;
+
+        let merged = &mut list[left.start..right.start + right.len];
+        let mid = left.len;
+
+        // the start of the left run that comes before all of the right run is already in place
+        let skip = gallop(&merged[..mid], false, |element| {
+            compare(&merged[mid], element) != Ordering::Less
+        });
+        if skip == mid {
+            return;
+        }
+        let merged = &mut merged[skip..];
+        let mid = mid - skip;
+
+        // and so is the end of the right run that comes after all of the left run
+        let keep = gallop(&merged[mid..], true, |element| {
+            compare(element, &merged[mid - 1]) == Ordering::Less
+        });
+        if keep == 0 {
+            return;
+        }
+        let merged = &mut merged[..mid + keep];
+
+        if mid <= keep {
+            self.merge_lo(merged, mid, compare);
+        } else {
+            self.merge_hi(merged, mid, compare);
+        }
+    }
+
+    /// Merges the sorted runs `list[..mid]` and `list[mid..]` from the front, moving the
+    /// left run into the buffer. Used when the left run is the shorter one.
+    ///
+    /// # Params
+    /// - `list` - The slice holding both runs.
+    /// - `mid` - The index that the right run starts at.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    fn merge_lo<F>(&mut self, list: &mut [T], mid: usize, compare: &F)
+    where
+        F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+    {
+        let len = list.len();
+        assert!(self.buffer.capacity() >= mid);
+
+        let list_ptr = list.as_mut_ptr();
+        let buffer_ptr = self.buffer.as_mut_ptr();
+
+        // SAFETY: like in `merge_with_buffer`, the left run is moved into the buffer, which
+        // leaves a gap the size of what is left in the buffer between `hole.dest` and
+        // `right`. Every step moves elements into the gap and moves the gap along by the
+        // same amount, and `MergeHole` fills the gap in again at the end (or if `compare`
+        // panics). The galloping searches only ever count elements that are really there,
+        // so they stay in bounds even if `compare` is inconsistent.
+        unsafe {
+            ptr::copy_nonoverlapping(list_ptr, buffer_ptr, mid);
+            let mut hole = MergeHole {
+                start: buffer_ptr,
+                end: buffer_ptr.add(mid),
+                dest: list_ptr,
+            };
+
+            let mut right = list_ptr.add(mid);
+            let right_end = list_ptr.add(len);
+            'merge: loop {
+                // take one element at a time until one run keeps winning
+                let mut left_wins = 0;
+                let mut right_wins = 0;
+                while left_wins < self.min_gallop && right_wins < self.min_gallop {
+                    if hole.start == hole.end || right == right_end {
+                        break 'merge;
+                    }
+
+                    // only take from the right run if it comes strictly first, which keeps this stable
+                    if compare(&*right, &*hole.start) == Ordering::Less {
+                        ptr::copy_nonoverlapping(right, hole.dest, 1);
+                        right = right.add(1);
+                        right_wins += 1;
+                        left_wins = 0;
+                    } else {
+                        ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                        hole.start = hole.start.add(1);
+                        left_wins += 1;
+                        right_wins = 0;
+                    }
+                    hole.dest = hole.dest.add(1);
+                }
+
+                // then gallop, moving whole chunks of each run at once
+                loop {
+                    if hole.start == hole.end || right == right_end {
+                        break 'merge;
+                    }
+
+                    let left_len = hole.end.offset_from(hole.start) as usize;
+                    let left_count = gallop(
+                        slice::from_raw_parts(hole.start, left_len),
+                        false,
+                        |element| compare(&*right, element) != Ordering::Less,
+                    );
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, left_count);
+                    hole.start = hole.start.add(left_count);
+                    hole.dest = hole.dest.add(left_count);
+                    if hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    // the next element of the right run comes first now
+                    ptr::copy_nonoverlapping(right, hole.dest, 1);
+                    right = right.add(1);
+                    hole.dest = hole.dest.add(1);
+                    if right == right_end {
+                        break 'merge;
+                    }
+
+                    let right_len = right_end.offset_from(right) as usize;
+                    let right_count =
+                        gallop(slice::from_raw_parts(right, right_len), false, |element| {
+                            compare(element, &*hole.start) == Ordering::Less
+                        });
+                    // the gap can be shorter than the chunk, so they may overlap
+                    ptr::copy(right, hole.dest, right_count);
+                    right = right.add(right_count);
+                    hole.dest = hole.dest.add(right_count);
+                    if right == right_end {
+                        break 'merge;
+                    }
+
+                    // and the next element of the left run comes first now
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                    hole.start = hole.start.add(1);
+                    hole.dest = hole.dest.add(1);
+
+                    if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                        // galloping isn't paying off, so make it harder to start again
+                        self.min_gallop += 2;
+                        break;
+                    }
+                    self.min_gallop = usize::max(self.min_gallop - 1, 1);
+                }
+            }
+        }
+    }
+
+    /// Merges the sorted runs `list[..mid]` and `list[mid..]` from the back, moving the
+    /// right run into the buffer. Used when the right run is the shorter one.
+    ///
+    /// # Params
+    /// - `list` - The slice holding both runs.
+    /// - `mid` - The index that the right run starts at.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    fn merge_hi<F>(&mut self, list: &mut [T], mid: usize, compare: &F)
+    where
+        F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+    {
+        let len = list.len();
+        assert!(self.buffer.capacity() >= len - mid);
+
+        let list_ptr = list.as_mut_ptr();
+        let buffer_ptr = self.buffer.as_mut_ptr();
+
+        // SAFETY: the same as `merge_lo`, but the gap is between `hole.dest` and `out`, and
+        // moves towards the front of the list.
+        unsafe {
+            ptr::copy_nonoverlapping(list_ptr.add(mid), buffer_ptr, len - mid);
+            let mut hole = MergeHole {
+                start: buffer_ptr,
+                end: buffer_ptr.add(len - mid),
+                dest: list_ptr.add(mid),
+            };
+
+            let mut out = list_ptr.add(len);
+            'merge: loop {
+                // take one element at a time until one run keeps winning
+                let mut left_wins = 0;
+                let mut right_wins = 0;
+                while left_wins < self.min_gallop && right_wins < self.min_gallop {
+                    if hole.dest == list_ptr || hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    out = out.sub(1);
+                    // only take from the left run if it comes strictly last, which keeps this stable
+                    if compare(&*hole.end.sub(1), &*hole.dest.sub(1)) == Ordering::Less {
+                        hole.dest = hole.dest.sub(1);
+                        ptr::copy_nonoverlapping(hole.dest, out, 1);
+                        left_wins += 1;
+                        right_wins = 0;
+                    } else {
+                        hole.end = hole.end.sub(1);
+                        ptr::copy_nonoverlapping(hole.end, out, 1);
+                        right_wins += 1;
+                        left_wins = 0;
+                    }
+                }
+
+                // then gallop, moving whole chunks of each run at once
+                loop {
+                    if hole.dest == list_ptr || hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    let left_len = hole.dest.offset_from(list_ptr) as usize;
+                    let left_count = left_len
+                        - gallop(slice::from_raw_parts(list_ptr, left_len), true, |element| {
+                            compare(&*hole.end.sub(1), element) != Ordering::Less
+                        });
+                    hole.dest = hole.dest.sub(left_count);
+                    out = out.sub(left_count);
+                    // the gap can be shorter than the chunk, so they may overlap
+                    ptr::copy(hole.dest, out, left_count);
+                    if hole.dest == list_ptr {
+                        break 'merge;
+                    }
+
+                    // the last element of the right run comes last now
+                    hole.end = hole.end.sub(1);
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(hole.end, out, 1);
+                    if hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    let right_len = hole.end.offset_from(hole.start) as usize;
+                    let right_count = right_len
+                        - gallop(
+                            slice::from_raw_parts(hole.start, right_len),
+                            true,
+                            |element| compare(element, &*hole.dest.sub(1)) == Ordering::Less,
+                        );
+                    hole.end = hole.end.sub(right_count);
+                    out = out.sub(right_count);
+                    ptr::copy_nonoverlapping(hole.end, out, right_count);
+                    if hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    // and the last element of the left run comes last now
+                    hole.dest = hole.dest.sub(1);
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(hole.dest, out, 1);
+
+                    if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                        // galloping isn't paying off, so make it harder to start again
+                        self.min_gallop += 2;
+                        break;
+                    }
+                    self.min_gallop = usize::max(self.min_gallop - 1, 1);
+                }
+            }
+        }
+    }
+}
+
+/// [`Sorter`] for [`tim_sort`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimSort;
+
+impl<T> Sorter<T> for TimSort {
+    fn name(&self) -> &'static str {
+        "Timsort"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            stable: true,
+            in_place: false,
+            parallel: false,
+        }
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        tim_sort_by(list, compare);
+    }
+}
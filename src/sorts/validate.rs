@@ -0,0 +1,271 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::sorts::{error::SortError, Capabilities, Sorter};
+
+/// A way that a comparator failed to describe a consistent order, along with the elements
+/// that show it. Found by [`validate_comparator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparatorViolation<'a, T> {
+    /// `compare(element, element)` wasn't `Equal`, so the element comes before (or after) itself.
+    Irreflexivity { element: &'a T },
+    /// `compare(first, second)` wasn't the reverse of `compare(second, first)`, for example
+    /// because both said `Less`.
+    Asymmetry { first: &'a T, second: &'a T },
+    /// `first` comes no later than `second`, and `second` comes no later than `third`,
+    /// but `compare(first, third)` doesn't agree.
+    Transitivity {
+        first: &'a T,
+        second: &'a T,
+        third: &'a T,
+    },
+}
+
+impl<T: fmt::Debug> fmt::Display for ComparatorViolation<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComparatorViolation::Irreflexivity { element } => {
+                write!(f, "{:?} is not equal to itself", element)
+            }
+            ComparatorViolation::Asymmetry { first, second } => write!(
+                f,
+                "comparing {:?} with {:?} doesn't give the reverse of comparing them the other way",
+                first, second
+            ),
+            ComparatorViolation::Transitivity {
+                first,
+                second,
+                third,
+            } => write!(
+                f,
+                "{:?}, {:?} and {:?} are in order pairwise, but {:?} and {:?} are not",
+                first, second, third, first, third
+            ),
+        }
+    }
+}
+
+/// Checks that a comparator describes a consistent order (a strict weak ordering) on a
+/// sample of elements. Every sort in `sorts` assumes this, and can leave the list out of
+/// order if it doesn't hold.
+///
+/// Every element, pair and triple of the sample is checked, so the sample should be kept
+/// fairly small (a few dozen elements).
+///
+/// # Params
+/// - `sample` - The elements to check the comparator on.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - `Ok(())` if the comparator is consistent on the sample, or the first violation found.
+///
+/// # Notes
+/// The `in_order` versions of the sorts turn their closure into an `Ordering` with
+/// [`ordering_from_in_order`](crate::sorts::ordering_from_in_order), so check an
+/// `in_order` closure by passing it through that first.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::validate::{validate_comparator, ComparatorViolation};
+///
+/// let sample = vec![3, 1, 2];
+/// assert_eq!(Ok(()), validate_comparator(&sample, |num1: &i32, num2: &i32| num1.cmp(num2)));
+///
+/// // claims everything comes first
+/// let result = validate_comparator(&sample, |_: &i32, _: &i32| std::cmp::Ordering::Less);
+/// assert_eq!(Err(ComparatorViolation::Irreflexivity { element: &3 }), result);
+///
+/// ```
+pub fn validate_comparator<T, F>(sample: &[T], compare: F) -> Result<(), ComparatorViolation<'_, T>>
+where
+    F: Fn(&T, &T) -> Ordering, // we want a closure to compare the two values and return an ordering
+{
+    let sample: Vec<&T> = sample.iter().collect();
+    find_violation(&sample, &compare)
+}
+
+/// Checks every element, pair and triple of a sample. Helper function for [`validate_comparator`]
+/// that works on references, so that a sample can be picked out of a list without cloning it.
+///
+/// # Params
+/// - `sample` - The elements to check the comparator on.
+/// - `compare` - The closure that returns how its `first` argument is ordered relative
+///   to its `second` argument.
+///
+/// # Returns
+/// - `Ok(())` if the comparator is consistent on the sample, or the first violation found.
+fn find_violation<'a, T, F>(sample: &[&'a T], compare: &F) -> Result<(), ComparatorViolation<'a, T>>
+where
+    F: Fn(&T, &T) -> Ordering + ?Sized, // we want a closure to compare the two values and return an ordering
+{
+    for &element in sample {
+        if compare(element, element) != Ordering::Equal {
+            return Err(ComparatorViolation::Irreflexivity { element });
+        }
+    }
+
+    for (i, &first) in sample.iter().enumerate() {
+        for &second in &sample[i + 1..] {
+            if compare(first, second) != compare(second, first).reverse() {
+                return Err(ComparatorViolation::Asymmetry { first, second });
+            }
+        }
+    }
+
+    // every pair is consistent by now, so every ordered triple is covered by trying each
+    // element in each position
+    for &first in sample {
+        for &second in sample {
+            let first_to_second = compare(first, second);
+            if first_to_second == Ordering::Greater {
+                continue;
+            }
+            for &third in sample {
+                let second_to_third = compare(second, third);
+                if second_to_third == Ordering::Greater {
+                    continue;
+                }
+
+                // `Equal` only if both steps are, otherwise `first` must come strictly first
+                if compare(first, third) != first_to_second.min(second_to_third) {
+                    return Err(ComparatorViolation::Transitivity {
+                        first,
+                        second,
+                        third,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs any [`Sorter`] with its comparator checked for consistency. Meant for debugging a
+/// comparator, as it makes every comparison twice and sorts noticeably slower.
+///
+/// Before sorting, [`validate_comparator`] is run on a sample spread across the list.
+/// While sorting, every comparison is checked against the comparison the other way
+/// around, and afterwards the list is checked to really be in order.
+///
+/// # Example
+///
+/// ```rust
+/// use sorting_algorithms::sorts::{error::SortError, quicksort::QuickSort, validate::Checked};
+///
+/// let checked = Checked { sorter: QuickSort };
+/// let mut list = vec![4, 5, 2, 1, 3];
+/// assert_eq!(Ok(()), checked.try_sort_by(&mut list, &|num1, num2| num1.cmp(num2)));
+/// assert_eq!(vec![1, 2, 3, 4, 5], list);
+///
+/// // a comparator that claims everything comes first is caught
+/// let result = checked.try_sort_by(&mut list, &|_, _| std::cmp::Ordering::Less);
+/// assert_eq!(
+///     Err(SortError::InconsistentComparator(String::from("1 is not equal to itself"))),
+///     result
+/// );
+///
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checked<S> {
+    /// The sorter to run.
+    pub sorter: S,
+}
+
+impl<S> Checked<S> {
+    /// Sorts the list with the wrapped sorter, checking the comparator along the way.
+    ///
+    /// # Params
+    /// - `list` - The slice to sort.
+    /// - `compare` - The closure that returns how its `first` argument is ordered relative
+    ///   to its `second` argument.
+    ///
+    /// # Returns
+    /// - `Ok(())` once the list is sorted, or [`SortError::InconsistentComparator`] if the
+    ///   comparator was caught being inconsistent, describing the first elements that showed
+    ///   it. The list still holds every one of its elements in that case, but may not be in
+    ///   order.
+    pub fn try_sort_by<T>(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError>
+    where
+        T: fmt::Debug,
+        S: Sorter<T>,
+    {
+        // the number of elements to run the full validation on before sorting
+        static SAMPLE_LEN: usize = 16;
+
+        let step = usize::max(list.len() / SAMPLE_LEN, 1);
+        let sample: Vec<&T> = list.iter().step_by(step).take(SAMPLE_LEN).collect();
+        if let Err(violation) = find_violation(&sample, compare) {
+            return Err(SortError::InconsistentComparator(violation.to_string()));
+        }
+
+        // the first comparison that didn't agree with itself the other way around, which is
+        // kept over anything found after sorting, as it's the cause
+        let first_violation: Mutex<Option<String>> = Mutex::new(None);
+        self.sorter.sort_by(list, &|first, second| {
+            let ordering = compare(first, second);
+            if ordering != compare(second, first).reverse() {
+                let mut slot = first_violation
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                // the elements may be moved around by the time the sort is done, so they are
+                // described right away, but only the first time
+                if slot.is_none() {
+                    *slot = Some(ComparatorViolation::Asymmetry { first, second }.to_string());
+                }
+            }
+            ordering
+        });
+
+        if let Some(violation) = first_violation
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            return Err(SortError::InconsistentComparator(violation));
+        }
+        if let Some(pair) = list
+            .windows(2)
+            .find(|pair| compare(&pair[0], &pair[1]) == Ordering::Greater)
+        {
+            return Err(SortError::InconsistentComparator(format!(
+                "{:?} was sorted before {:?}, but comes after it",
+                pair[0], pair[1]
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug, S: Sorter<T>> Sorter<T> for Checked<S> {
+    fn name(&self) -> &'static str {
+        self.sorter.name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.sorter.capabilities()
+    }
+
+    fn sort_by(&self, list: &mut [T], compare: &(dyn Fn(&T, &T) -> Ordering + Sync)) {
+        if let Err(err_val) = self.try_sort_by(list, compare) {
+            panic!("Error when doing a checked sort ({})", err_val);
+        }
+    }
+
+    fn try_sort_by(
+        &self,
+        list: &mut [T],
+        compare: &(dyn Fn(&T, &T) -> Ordering + Sync),
+    ) -> Result<(), SortError> {
+        Checked::try_sort_by(self, list, compare)
+    }
+}
@@ -1,15 +1,60 @@
-// ignoring unused import and dead code warnings for tests
-#![allow(unused_imports)]
-#![allow(dead_code)]
-use std::fmt::Debug;
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::HashSet,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicIsize, AtomicUsize, Ordering as AtomicOrdering},
+        Mutex,
+    },
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
 
 use crate::{
-    bubble_sort, insertion_sort, merge_sort_top_down, selection_sort,
+    helpers::{generate_rand_vec, is_sorted},
     sorts::{
-        heapsort::heap_sort, mergesort::merge_sort_top_down_multithread, quicksort::quick_sort,
+        all_sorters, auto_thread_count,
+        bubblesort::{bubble_sort, bubble_sort_by, bubble_sort_by_key, bubble_sort_ord},
+        error::SortError,
+        heapsort::{
+            heap_sort, heap_sort_by, heap_sort_by_key, heap_sort_in_place, heap_sort_ord, Heap,
+            HeapSort,
+        },
+        insertionsort::{
+            binary_insertion_sort_by, insertion_sort, insertion_sort_by, insertion_sort_by_key,
+            insertion_sort_ord, InsertionSort,
+        },
+        introsort::{intro_sort, intro_sort_by, intro_sort_by_key, intro_sort_ord},
+        mergesort::{
+            merge_k_sorted, merge_sort_bottom_up, merge_sort_bottom_up_by,
+            merge_sort_bottom_up_by_key, merge_sort_bottom_up_ord, merge_sort_top_down,
+            merge_sort_top_down_by, merge_sort_top_down_by_key, merge_sort_top_down_multithread,
+            merge_sort_top_down_multithread_auto, merge_sort_top_down_multithread_by,
+            merge_sort_top_down_ord, merge_sort_top_down_pooled, MergeIter, MergeSortBottomUp,
+            MergeSortMultithread, MergeSortPooled,
+        },
+        ordering_from_in_order,
+        pdqsort::{pdq_sort, pdq_sort_by, pdq_sort_by_key, pdq_sort_ord},
+        pool::{default_pool, SortPool},
+        quicksort::{
+            dual_pivot_quick_sort, dual_pivot_quick_sort_by, dual_pivot_quick_sort_by_key,
+            dual_pivot_quick_sort_ord, quick_sort, quick_sort_by, quick_sort_by_key,
+            quick_sort_in_place, quick_sort_ord, quick_sort_parallel, quick_sort_parallel_auto,
+            quick_sort_parallel_by, quick_sort_pooled, quick_sort_three_way,
+            quick_sort_three_way_by, quick_sort_three_way_by_key, quick_sort_three_way_ord,
+            QuickSort, QuickSortParallel, QuickSortPooled,
+        },
+        radixsort::{radix_sort, radix_sort_by_key},
+        selectionsort::{
+            selection_sort, selection_sort_by, selection_sort_by_key, selection_sort_ord,
+        },
+        stable_sort, stable_sort_by, stable_sort_by_key, stable_sort_ord,
+        timsort::{tim_sort, tim_sort_by, tim_sort_by_key, tim_sort_ord},
+        validate::{validate_comparator, Checked, ComparatorViolation},
+        Sorter,
     },
 };
-use rand::Rng;
 
 static BASIC_SORT_RAND_VEC_LEN: u32 = 4000;
 static ADVANCED_SORT_RAND_VEC_LEN: u32 = 40000;
@@ -40,7 +85,7 @@ fn insertion_sort_test() {
 
     let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
     insertion_sort(&mut list, ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, ascending_sort_closure));
+    assert!(is_sorted(&list, ascending_sort_closure));
 }
 
 #[test]
@@ -69,7 +114,7 @@ fn bubble_sort_test() {
 
     let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
     bubble_sort(&mut list, ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, ascending_sort_closure));
+    assert!(is_sorted(&list, ascending_sort_closure));
 }
 
 #[test]
@@ -98,7 +143,7 @@ fn selection_sort_test() {
 
     let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
     selection_sort(&mut list, ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, ascending_sort_closure));
+    assert!(is_sorted(&list, ascending_sort_closure));
 }
 
 #[test]
@@ -131,41 +176,268 @@ fn merge_sort_test() {
     // since this algorithm is faster than the others, let's push it a little
     let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
     list = merge_sort_top_down(&list, &ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, ascending_sort_closure));
+    assert!(is_sorted(&list, ascending_sort_closure));
+}
+
+#[test]
+fn merge_sort_bottom_up_test() {
+    let ascending_sort_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_sort_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    // create a list of elements
+    let mut list = vec![4, 5, 2, 1, 3];
+    // sort using a closure to sort elements in ascending order
+    merge_sort_bottom_up(&mut list, ascending_sort_closure);
+    assert_eq!(vec![1, 2, 3, 4, 5], list);
+
+    let mut list: Vec<i32> = vec![];
+    merge_sort_bottom_up(&mut list, ascending_sort_closure);
+    let test: Vec<i32> = vec![];
+    assert_eq!(test, list);
+
+    let mut list: Vec<i32> = vec![1];
+    merge_sort_bottom_up(&mut list, ascending_sort_closure);
+    assert_eq!(vec![1], list);
+
+    // descending order sort
+    let mut list: Vec<i32> = vec![4, 5, 2, 1, 3];
+    merge_sort_bottom_up(&mut list, descending_sort_closure);
+    assert_eq!(vec![5, 4, 3, 2, 1], list);
+
+    // lengths that leave a short run at the end, which gets merged from the back
+    for len in [17, 100, 1000, 1025] {
+        let mut list = generate_rand_vec(len);
+        merge_sort_bottom_up(&mut list, ascending_sort_closure);
+        assert!(is_sorted(&list, ascending_sort_closure));
+    }
+
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    merge_sort_bottom_up(&mut list, ascending_sort_closure);
+    assert!(is_sorted(&list, ascending_sort_closure));
+
+    // elements are moved rather than cloned, and equal keys keep their order
+    #[derive(Debug)]
+    struct Record {
+        key: i32,
+        idx: usize,
+    }
+    let mut list: Vec<Record> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, num)| Record {
+            key: num % 100,
+            idx,
+        })
+        .collect();
+    merge_sort_bottom_up_by_key(&mut list, |record| record.key);
+    assert!(is_sorted(&list, |record1: &Record, record2: &Record| {
+        record1.key < record2.key || (record1.key == record2.key && record1.idx < record2.idx)
+    }));
+}
+
+#[test]
+fn merge_k_sorted_test() {
+    let ascending_sort_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+
+    // lots of shards, each sorted on its own
+    let mut shards = vec![];
+    for _ in 0..32 {
+        let mut shard = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN / 4);
+        quick_sort_ord(&mut shard);
+        shards.push(shard);
+    }
+    let total_len: usize = shards.iter().map(Vec::len).sum();
+    let merged = merge_k_sorted(shards, |num1: &i32, num2: &i32| num1.cmp(num2));
+    assert_eq!(total_len, merged.len());
+    assert!(is_sorted(&merged, ascending_sort_closure));
+
+    // empty lists, and no lists at all
+    let merged = merge_k_sorted(vec![vec![], vec![3], vec![]], |num1: &i32, num2| {
+        num1.cmp(num2)
+    });
+    assert_eq!(vec![3], merged);
+    let merged = merge_k_sorted(Vec::<Vec<i32>>::new(), |num1, num2| num1.cmp(num2));
+    assert!(merged.is_empty());
+
+    // iterators, merged in descending order
+    let merged = merge_k_sorted([(0..5).rev(), (3..8).rev()], |num1, num2| num2.cmp(num1));
+    assert_eq!(vec![7, 6, 5, 4, 4, 3, 3, 2, 1, 0], merged);
+
+    // equal keys come out in the order of the lists, then the order within each list
+    let lists = vec![
+        vec![(1, "a0"), (2, "a1"), (2, "a2")],
+        vec![(1, "b0"), (2, "b1")],
+        vec![(2, "c0")],
+    ];
+    let merged = merge_k_sorted(lists, |pair1: &(i32, &str), pair2| pair1.0.cmp(&pair2.0));
+    assert_eq!(
+        vec![
+            (1, "a0"),
+            (1, "b0"),
+            (2, "a1"),
+            (2, "a2"),
+            (2, "b1"),
+            (2, "c0")
+        ],
+        merged
+    );
+}
+
+#[test]
+fn merge_iter_test() {
+    // the sources are never collected, so they can even be endless
+    let evens = (0..).step_by(2);
+    let odds = (1..).step_by(2);
+    let merged: Vec<i32> = MergeIter::new([evens, odds], |num1, num2| num1.cmp(num2))
+        .take(10)
+        .collect();
+    assert_eq!((0..10).collect::<Vec<i32>>(), merged);
+
+    // dropping duplicates, both within a source and across sources
+    let lists = vec![vec![1, 1, 2, 5], vec![1, 3, 5, 5], vec![], vec![2, 6]];
+    let merged: Vec<i32> = MergeIter::new(lists.clone(), |num1, num2| num1.cmp(num2))
+        .dedup()
+        .collect();
+    assert_eq!(vec![1, 2, 3, 5, 6], merged);
+
+    let merge_iter = MergeIter::new(lists, |num1, num2| num1.cmp(num2));
+    assert_eq!((10, Some(10)), merge_iter.size_hint());
+    assert_eq!(10, merge_iter.count());
+
+    // random sorted streams give the same result as merging them all at once
+    let mut shards = vec![];
+    for _ in 0..8 {
+        let mut shard = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN / 4);
+        quick_sort_by(&mut shard, |num1, num2| num2.cmp(num1));
+        shards.push(shard);
+    }
+    let expected = merge_k_sorted(shards.clone(), |num1: &i32, num2| num2.cmp(num1));
+    let merged: Vec<i32> = MergeIter::new(shards, |num1, num2| num2.cmp(num1)).collect();
+    assert_eq!(expected, merged);
+    assert!(is_sorted(&merged, |num1: &i32, num2: &i32| num1 >= num2));
 }
 
 #[test]
 fn merge_sort_multithreaded_test() {
-    let num_threads = 8; // This is the sweet spot on my machine that typically gets the best results
-    let list = generate_rand_vec(400000);
+    let mut list = generate_rand_vec(400000);
     let closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
 
-    let merge_sort_result = merge_sort_top_down_multithread(
-        list,
-        // evil static closure
-        &|num1: &i32, num2: &i32| -> bool { num1 <= num2 },
-        num_threads,
-    );
+    // let the sort pick the number of threads for this machine
+    let merge_sort_result = merge_sort_top_down_multithread_auto(&mut list, &closure);
 
     match merge_sort_result {
-        Ok(merged_list) => {
-            assert_eq!(true, is_sorted(&merged_list, closure))
+        Ok(report) => {
+            assert!(is_sorted(&list, closure));
+            assert_eq!(list.len(), report.list_len);
+            assert!(report.num_threads >= 1);
+            assert!(report.num_threads <= report.available_parallelism);
         }
         Err(err_val) => {
             panic!("Error when doing multithreaded merge sort ({})", err_val);
         }
     }
+
+    // short lists don't get extra threads
+    let mut list = generate_rand_vec(100);
+    let report = merge_sort_top_down_multithread_auto(&mut list, &closure)
+        .expect("Error when doing multithreaded merge sort");
+    assert!(is_sorted(&list, closure));
+    assert_eq!(1, report.num_threads);
+    assert_eq!(report.available_parallelism > 1, report.limited_by_list_len);
+
+    // a panic on the calling thread is reported like one on a worker thread
+    let panicking_result =
+        merge_sort_top_down_multithread_auto(&mut list, &|_: &i32, _: &i32| -> bool {
+            panic!("comparator panicked")
+        });
+    assert_eq!(
+        Err(SortError::WorkerPanicked(String::from(
+            "comparator panicked"
+        ))),
+        panicking_result
+    );
+
+    let report = auto_thread_count(0);
+    assert_eq!(1, report.num_threads);
+
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    let report = quick_sort_parallel_auto(&mut list, &closure)
+        .expect("Error when doing multithreaded quicksort");
+    assert!(is_sorted(&list, closure));
+    assert_eq!(auto_thread_count(list.len()), report);
 }
 
-/// Generates a random vector of `i32`s.
-pub fn generate_rand_vec(num_elements: u32) -> Vec<i32> {
-    let mut rng = rand::thread_rng();
-    let mut output = vec![];
-    for _ in 0..num_elements {
-        output.push(rng.gen_range(i32::MIN..=i32::MAX));
+#[test]
+fn merge_sort_multithreaded_tree_merge_test() {
+    let closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+
+    // odd thread counts leave a chunk out of some merge rounds, and more threads than
+    // elements leave some chunks empty
+    for num_threads in [1, 2, 3, 5, 13, 16] {
+        for len in [0, 1, 10, ADVANCED_SORT_RAND_VEC_LEN] {
+            let mut list = generate_rand_vec(len);
+            let merge_sort_result =
+                merge_sort_top_down_multithread(&mut list, &closure, num_threads);
+            match merge_sort_result {
+                Ok(()) => {
+                    assert_eq!(len as usize, list.len());
+                    assert!(is_sorted(&list, closure));
+                }
+                Err(err_val) => {
+                    panic!("Error when doing multithreaded merge sort ({})", err_val);
+                }
+            }
+        }
     }
 
-    output
+    // equal elements keep their order across the chunks
+    let mut list: Vec<(i32, usize)> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, num)| (num % 10, idx))
+        .collect();
+    merge_sort_top_down_multithread_by(&mut list, |pair1, pair2| pair1.0.cmp(&pair2.0), 7)
+        .expect("Error when doing multithreaded merge sort");
+    assert!(is_sorted(&list, |pair1: &(i32, usize), pair2| {
+        pair1.0 < pair2.0 || (pair1.0 == pair2.0 && pair1.1 < pair2.1)
+    }));
+
+    // the comparator can borrow local configuration, and the list can be a borrowed slice
+    let descending = true;
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    let half = list.len() / 2;
+    merge_sort_top_down_multithread(
+        &mut list[..half],
+        &|num1: &i32, num2: &i32| -> bool {
+            if descending {
+                num1 >= num2
+            } else {
+                num1 <= num2
+            }
+        },
+        4,
+    )
+    .expect("Error when doing multithreaded merge sort");
+    assert!(is_sorted(&list[..half], |num1: &i32, num2: &i32| num1 >= num2));
+
+    assert_eq!(
+        Err(SortError::InvalidThreadCount),
+        merge_sort_top_down_multithread(&mut [1], &closure, 0)
+    );
+
+    // a panicking comparator is reported as an error instead of tearing down the caller
+    let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+    let panicking_result = merge_sort_top_down_multithread(
+        &mut list,
+        &|_: &i32, _: &i32| -> bool { panic!("comparator panicked") },
+        4,
+    );
+    assert_eq!(
+        Err(SortError::WorkerPanicked(String::from(
+            "comparator panicked"
+        ))),
+        panicking_result
+    );
 }
 
 #[test]
@@ -197,11 +469,617 @@ fn quick_sort_test() {
 
     let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
     list = quick_sort(list, &ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, &ascending_is_eq_closure));
+    assert!(is_sorted(&list, &ascending_is_eq_closure));
 
     let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
     list = quick_sort(list, &descending_sort_closure);
-    assert_eq!(true, is_sorted(&list, &descending_is_eq_closure));
+    assert!(is_sorted(&list, &descending_is_eq_closure));
+}
+
+#[test]
+fn quick_sort_in_place_test() {
+    // a type that can't be cloned, which the in place quicksort doesn't need
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Handle(i32);
+
+    let mut list: Vec<Handle> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(Handle)
+        .collect();
+    quick_sort_by(&mut list, |handle1, handle2| handle1.cmp(handle2));
+    assert!(is_sorted(&list, |handle1: &Handle, handle2: &Handle| {
+        handle1 <= handle2
+    }));
+
+    // already sorted, reversed and all equal lists
+    let mut list: Vec<i32> = (0..ADVANCED_SORT_RAND_VEC_LEN as i32).collect();
+    quick_sort_ord(&mut list);
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| num1 <= num2));
+
+    list.reverse();
+    quick_sort_ord(&mut list);
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| num1 <= num2));
+
+    let mut list = vec![7; ADVANCED_SORT_RAND_VEC_LEN as usize];
+    quick_sort_ord(&mut list);
+    assert_eq!(vec![7; ADVANCED_SORT_RAND_VEC_LEN as usize], list);
+
+    // the bool version works on a borrowed slice too
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    quick_sort_in_place(&mut list, |num1, num2| num1 > num2);
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| num1 >= num2));
+}
+
+#[test]
+fn quick_sort_parallel_test() {
+    let closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+
+    // short lists are sorted on the calling thread, long ones get split between the workers
+    for num_threads in [1, 2, 3, 8] {
+        for len in [
+            0,
+            1,
+            10,
+            ADVANCED_SORT_RAND_VEC_LEN,
+            ADVANCED_SORT_RAND_VEC_LEN * 5,
+        ] {
+            let mut list = generate_rand_vec(len);
+            let quick_sort_result = quick_sort_parallel(&mut list, &closure, num_threads);
+            match quick_sort_result {
+                Ok(()) => {
+                    assert_eq!(len as usize, list.len());
+                    assert!(is_sorted(&list, closure));
+                }
+                Err(err_val) => {
+                    panic!("Error when doing multithreaded quicksort ({})", err_val);
+                }
+            }
+        }
+    }
+
+    // lots of duplicates, and lists that are already in order
+    let mut list: Vec<i32> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(|num| num % 4)
+        .collect();
+    quick_sort_parallel_by(&mut list, |num1, num2| num1.cmp(num2), 4)
+        .expect("Error when doing multithreaded quicksort");
+    assert!(is_sorted(&list, closure));
+
+    let mut list: Vec<i32> = (0..ADVANCED_SORT_RAND_VEC_LEN as i32).collect();
+    quick_sort_parallel_by(&mut list, |num1, num2| num2.cmp(num1), 4)
+        .expect("Error when doing multithreaded quicksort");
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| num1 >= num2));
+
+    assert_eq!(
+        Err(SortError::InvalidThreadCount),
+        quick_sort_parallel(&mut [1], &closure, 0)
+    );
+
+    // a panicking comparator is reported as an error instead of tearing down the caller
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    let panicking_result = quick_sort_parallel(
+        &mut list,
+        &|_: &i32, _: &i32| -> bool { panic!("comparator panicked") },
+        4,
+    );
+    assert_eq!(
+        Err(SortError::WorkerPanicked(String::from(
+            "comparator panicked"
+        ))),
+        panicking_result
+    );
+
+    // and so is one on a list that is sorted on the calling thread
+    for (len, num_threads) in [(ADVANCED_SORT_RAND_VEC_LEN, 1), (10, 4)] {
+        let mut list = generate_rand_vec(len);
+        let panicking_result = quick_sort_parallel(
+            &mut list,
+            &|_: &i32, _: &i32| -> bool { panic!("comparator panicked") },
+            num_threads,
+        );
+        assert_eq!(
+            Err(SortError::WorkerPanicked(String::from(
+                "comparator panicked"
+            ))),
+            panicking_result
+        );
+        assert_eq!(len as usize, list.len());
+    }
+}
+
+#[test]
+fn sort_pool_test() {
+    let closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let pool = SortPool::new(3).expect("Error when creating the sort pool");
+    assert_eq!(3, pool.num_threads());
+    assert!(matches!(
+        SortPool::new(0),
+        Err(SortError::InvalidThreadCount)
+    ));
+
+    // the same workers are reused for every sort
+    for len in [
+        0,
+        1,
+        10,
+        BASIC_SORT_RAND_VEC_LEN,
+        ADVANCED_SORT_RAND_VEC_LEN,
+    ] {
+        let mut list = generate_rand_vec(len);
+        merge_sort_top_down_pooled(&mut list, &closure, &pool)
+            .expect("Error when doing pooled merge sort");
+        assert_eq!(len as usize, list.len());
+        assert!(is_sorted(&list, closure));
+
+        let mut list = generate_rand_vec(len);
+        quick_sort_pooled(&mut list, &closure, &pool).expect("Error when doing pooled quicksort");
+        assert_eq!(len as usize, list.len());
+        assert!(is_sorted(&list, closure));
+    }
+
+    // sorts from different threads can share the pool at the same time
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..5 {
+                    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+                    quick_sort_pooled(&mut list, &closure, &pool)
+                        .expect("Error when doing pooled quicksort");
+                    assert!(is_sorted(&list, closure));
+                }
+            });
+        }
+    });
+
+    // the sorts that aren't handed a pool all share the default one, so sorting from many
+    // threads at once doesn't start any more threads than that
+    let sorting_threads: Mutex<HashSet<ThreadId>> = Mutex::new(HashSet::new());
+    let recording_closure = |num1: &i32, num2: &i32| -> bool {
+        sorting_threads
+            .lock()
+            .unwrap()
+            .insert(thread::current().id());
+        num1 <= num2
+    };
+    let num_callers = 4;
+    thread::scope(|scope| {
+        for _ in 0..num_callers {
+            scope.spawn(|| {
+                for _ in 0..3 {
+                    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN / 2);
+                    quick_sort_parallel(&mut list, &recording_closure, 4)
+                        .expect("Error when doing multithreaded quicksort");
+                    assert!(is_sorted(&list, closure));
+
+                    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN / 2);
+                    merge_sort_top_down_multithread(&mut list, &recording_closure, 4)
+                        .expect("Error when doing multithreaded merge sort");
+                    assert!(is_sorted(&list, closure));
+                }
+            });
+        }
+    });
+    let default_pool = default_pool().expect("Error when starting the default sort pool");
+    assert!(
+        sorting_threads.lock().unwrap().len() <= default_pool.num_threads() as usize + num_callers
+    );
+
+    // a panicking comparator is reported, and the pool keeps working afterwards
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    let panicking_result = merge_sort_top_down_pooled(
+        &mut list,
+        &|_: &i32, _: &i32| -> bool { panic!("comparator panicked") },
+        &pool,
+    );
+    assert_eq!(
+        Err(SortError::WorkerPanicked(String::from(
+            "comparator panicked"
+        ))),
+        panicking_result
+    );
+
+    // including when the pool is skipped, for a short list or a single worker
+    let single_pool = SortPool::new(1).expect("Error when creating the sort pool");
+    for (len, pool) in [(10, &pool), (ADVANCED_SORT_RAND_VEC_LEN, &single_pool)] {
+        let mut list = generate_rand_vec(len);
+        let panicking_result = quick_sort_pooled(
+            &mut list,
+            &|_: &i32, _: &i32| -> bool { panic!("comparator panicked") },
+            pool,
+        );
+        assert_eq!(
+            Err(SortError::WorkerPanicked(String::from(
+                "comparator panicked"
+            ))),
+            panicking_result
+        );
+    }
+
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    merge_sort_top_down_pooled(&mut list, &closure, &pool)
+        .expect("Error when doing pooled merge sort");
+    assert!(is_sorted(&list, closure));
+
+    // jobs can borrow from the caller and spawn more jobs
+    let mut counts = [0; 8];
+    pool.scope(|scope| {
+        for (idx, count) in counts.iter_mut().enumerate() {
+            scope.spawn(move || {
+                *count = idx;
+                scope.spawn(|| {});
+            });
+        }
+    })
+    .expect("Error when running jobs on the sort pool");
+    assert_eq!([0, 1, 2, 3, 4, 5, 6, 7], counts);
+
+    // jobs spawned by a busy worker are stolen by the other threads
+    let num_stolen = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        let num_stolen = &num_stolen;
+        scope.spawn(move || {
+            let spawner = thread::current().id();
+            for _ in 0..2 {
+                scope.spawn(move || {
+                    assert_ne!(spawner, thread::current().id());
+                    num_stolen.fetch_add(1, AtomicOrdering::SeqCst);
+                });
+            }
+
+            // stay busy until both have been stolen, which would never happen if only
+            // this worker could run them
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while num_stolen.load(AtomicOrdering::SeqCst) < 2 {
+                assert!(Instant::now() < deadline, "the jobs were never stolen");
+                thread::yield_now();
+            }
+        });
+    })
+    .expect("Error when running jobs on the sort pool");
+    assert_eq!(2, num_stolen.load(AtomicOrdering::SeqCst));
+}
+
+#[test]
+fn quick_sort_three_way_test() {
+    let ascending_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    for num_keys in [1, 2, 5, 100, i32::MAX] {
+        let mut list: Vec<i32> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+            .into_iter()
+            .map(|num| num % num_keys)
+            .collect();
+        quick_sort_three_way(&mut list, ascending_closure);
+        assert_eq!(BASIC_SORT_RAND_VEC_LEN as usize, list.len());
+        assert!(is_sorted(&list, ascending_closure));
+
+        quick_sort_three_way(&mut list, descending_closure);
+        assert!(is_sorted(&list, descending_closure));
+    }
+
+    // benchmark against the two-way quicksort on low cardinality data, like status codes
+    let status_codes = [200, 201, 204, 301, 404, 500];
+    let len = ADVANCED_SORT_RAND_VEC_LEN as usize;
+    let list: Vec<i32> = generate_rand_vec(len as u32)
+        .into_iter()
+        .map(|num| status_codes[num.unsigned_abs() as usize % status_codes.len()])
+        .collect();
+    let num_comparisons = Cell::new(0);
+    let counting_compare = |num1: &i32, num2: &i32| {
+        num_comparisons.set(num_comparisons.get() + 1);
+        num1.cmp(num2)
+    };
+
+    let mut two_way_list = list.clone();
+    quick_sort_by(&mut two_way_list, counting_compare);
+    let two_way_comparisons = num_comparisons.replace(0);
+
+    let mut three_way_list = list;
+    quick_sort_three_way_by(&mut three_way_list, counting_compare);
+    let three_way_comparisons = num_comparisons.get();
+
+    assert_eq!(two_way_list, three_way_list);
+    // every distinct key takes one pass at most, so this is linear in the length
+    assert!(three_way_comparisons < status_codes.len() * len);
+    assert!(three_way_comparisons < two_way_comparisons);
+}
+
+#[test]
+fn dual_pivot_quick_sort_test() {
+    let ascending_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    for len in [0, 1, 2, 99, 100, 101, BASIC_SORT_RAND_VEC_LEN as usize] {
+        let random = generate_rand_vec(len as u32);
+        let patterns: Vec<Vec<i32>> = vec![
+            random.clone(),
+            (0..len as i32).collect(),
+            (0..len as i32).rev().collect(),
+            vec![7; len],
+            random.iter().map(|num| num % 4).collect(),
+        ];
+
+        for mut list in patterns {
+            dual_pivot_quick_sort(&mut list, ascending_closure);
+            assert_eq!(len, list.len());
+            assert!(is_sorted(&list, ascending_closure));
+
+            dual_pivot_quick_sort(&mut list, descending_closure);
+            assert!(is_sorted(&list, descending_closure));
+        }
+    }
+
+    // compare against the single pivot quicksort, which should be in the same ballpark
+    let len = ADVANCED_SORT_RAND_VEC_LEN as usize;
+    let list = generate_rand_vec(len as u32);
+    let num_comparisons = Cell::new(0);
+    let counting_compare = |num1: &i32, num2: &i32| {
+        num_comparisons.set(num_comparisons.get() + 1);
+        num1.cmp(num2)
+    };
+
+    let mut single_pivot_list = list.clone();
+    quick_sort_by(&mut single_pivot_list, counting_compare);
+    let single_pivot_comparisons = num_comparisons.replace(0);
+
+    let mut dual_pivot_list = list;
+    dual_pivot_quick_sort_by(&mut dual_pivot_list, counting_compare);
+    let dual_pivot_comparisons = num_comparisons.get();
+
+    assert_eq!(single_pivot_list, dual_pivot_list);
+    let n_log_n = len * len.ilog2() as usize;
+    assert!(single_pivot_comparisons < 2 * n_log_n);
+    assert!(dual_pivot_comparisons < 2 * n_log_n);
+}
+
+#[test]
+fn intro_sort_test() {
+    let ascending_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    for len in [
+        0,
+        1,
+        2,
+        10,
+        BASIC_SORT_RAND_VEC_LEN,
+        ADVANCED_SORT_RAND_VEC_LEN,
+    ] {
+        let mut list = generate_rand_vec(len);
+        intro_sort(&mut list, ascending_closure);
+        assert_eq!(len as usize, list.len());
+        assert!(is_sorted(&list, ascending_closure));
+
+        intro_sort(&mut list, descending_closure);
+        assert!(is_sorted(&list, descending_closure));
+    }
+
+    // build an input that drives the quicksort to quadratic time, with McIlroy's adversary:
+    // every element starts out as "gas" that compares above everything else, and gets
+    // frozen to the next smallest value once it has to be told apart from the pivot
+    let len = 10000;
+    let gas = len;
+    let values = RefCell::new(vec![gas; len]);
+    let num_solid = Cell::new(0);
+    let candidate = Cell::new(0);
+    let mut indices: Vec<usize> = (0..len).collect();
+    quick_sort_by(&mut indices, |&idx1, &idx2| {
+        let mut values = values.borrow_mut();
+        if values[idx1] == gas && values[idx2] == gas {
+            let frozen = if idx1 == candidate.get() { idx1 } else { idx2 };
+            values[frozen] = num_solid.get();
+            num_solid.set(num_solid.get() + 1);
+        }
+        if values[idx1] == gas {
+            candidate.set(idx1);
+        } else if values[idx2] == gas {
+            candidate.set(idx2);
+        }
+        values[idx1].cmp(&values[idx2])
+    });
+    let killer_input = values.into_inner();
+
+    type SortBy = fn(&mut [usize], &dyn Fn(&usize, &usize) -> Ordering);
+    let count_comparisons = |sort_by: SortBy| {
+        let num_comparisons = Cell::new(0);
+        let mut list = killer_input.clone();
+        sort_by(&mut list, &|num1, num2| {
+            num_comparisons.set(num_comparisons.get() + 1);
+            num1.cmp(num2)
+        });
+        assert!(is_sorted(&list, |num1: &usize, num2: &usize| num1 <= num2));
+        num_comparisons.get()
+    };
+    let quick_sort_comparisons = count_comparisons(|list, compare| quick_sort_by(list, compare));
+    let intro_sort_comparisons = count_comparisons(|list, compare| intro_sort_by(list, compare));
+
+    // n log n with some room for the constant factor, which the quicksort blows way past
+    let n_log_n = len * len.ilog2() as usize;
+    assert!(quick_sort_comparisons > 10 * n_log_n);
+    assert!(intro_sort_comparisons < 4 * n_log_n);
+}
+
+#[test]
+fn pdq_sort_test() {
+    let ascending_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    // the patterns that pdqsort looks out for, at lengths around its block size
+    for len in [0, 1, 2, 19, 21, 100, 300, BASIC_SORT_RAND_VEC_LEN as usize] {
+        let random = generate_rand_vec(len as u32);
+        let patterns: Vec<Vec<i32>> = vec![
+            random.clone(),
+            (0..len as i32).collect(),
+            (0..len as i32).rev().collect(),
+            vec![7; len],
+            random.iter().map(|num| num % 4).collect(),
+            (0..len as i32).map(|num| num % 50).collect(),
+            (0..len as i32)
+                .map(|num| i32::min(num, len as i32 - num))
+                .collect(),
+        ];
+
+        for mut list in patterns {
+            pdq_sort(&mut list, ascending_closure);
+            assert_eq!(len, list.len());
+            assert!(is_sorted(&list, ascending_closure));
+
+            pdq_sort(&mut list, descending_closure);
+            assert!(is_sorted(&list, descending_closure));
+        }
+    }
+
+    // sorted and reversed lists are spotted and finished off in linear time
+    let len = ADVANCED_SORT_RAND_VEC_LEN as usize;
+    let num_comparisons = Cell::new(0);
+    let counting_compare = |num1: &usize, num2: &usize| {
+        num_comparisons.set(num_comparisons.get() + 1);
+        num1.cmp(num2)
+    };
+    for mut list in [(0..len).collect::<Vec<usize>>(), (0..len).rev().collect()] {
+        num_comparisons.set(0);
+        pdq_sort_by(&mut list, counting_compare);
+        assert!(is_sorted(&list, |num1: &usize, num2: &usize| num1 <= num2));
+        assert!(num_comparisons.get() < 2 * len);
+    }
+}
+
+#[test]
+fn tim_sort_test() {
+    let ascending_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    let descending_closure = |num1: &i32, num2: &i32| -> bool { num1 >= num2 };
+
+    // lengths around the shortest run length, and lists made of runs that have to be merged
+    for len in [
+        0,
+        1,
+        2,
+        63,
+        64,
+        65,
+        300,
+        ADVANCED_SORT_RAND_VEC_LEN as usize,
+    ] {
+        let random = generate_rand_vec(len as u32);
+        let patterns: Vec<Vec<i32>> = vec![
+            random.clone(),
+            (0..len as i32).collect(),
+            (0..len as i32).rev().collect(),
+            vec![7; len],
+            random.iter().map(|num| num % 4).collect(),
+            (0..len as i32).map(|num| num % 1000).collect(),
+            (0..len as i32).map(|num| -(num % 777)).collect(),
+        ];
+
+        for mut list in patterns {
+            let mut binary_list = list.clone();
+            binary_insertion_sort_by(&mut binary_list[..usize::min(len, 300)], i32::cmp);
+            assert!(is_sorted(
+                &binary_list[..usize::min(len, 300)],
+                ascending_closure
+            ));
+
+            tim_sort(&mut list, ascending_closure);
+            assert_eq!(len, list.len());
+            assert!(is_sorted(&list, ascending_closure));
+
+            tim_sort(&mut list, descending_closure);
+            assert!(is_sorted(&list, descending_closure));
+        }
+    }
+
+    // a sorted or reversed list is one run, so it takes a single pass
+    let len = ADVANCED_SORT_RAND_VEC_LEN as usize;
+    let num_comparisons = Cell::new(0);
+    let counting_compare = |num1: &usize, num2: &usize| {
+        num_comparisons.set(num_comparisons.get() + 1);
+        num1.cmp(num2)
+    };
+    for mut list in [(0..len).collect::<Vec<usize>>(), (0..len).rev().collect()] {
+        num_comparisons.set(0);
+        tim_sort_by(&mut list, counting_compare);
+        assert_eq!((0..len).collect::<Vec<usize>>(), list);
+        assert_eq!(len - 1, num_comparisons.get());
+    }
+
+    // two interleaved sorted halves are merged with galloping, well under n log n
+    let mut list: Vec<usize> = (0..len / 2).chain(len / 4..len / 4 + len / 2).collect();
+    num_comparisons.set(0);
+    tim_sort_by(&mut list, counting_compare);
+    assert!(is_sorted(&list, |num1: &usize, num2: &usize| num1 <= num2));
+    assert!(num_comparisons.get() < 2 * len);
+}
+
+#[test]
+fn radix_sort_test() {
+    // every integer type is compared against its own ordering, including the extremes
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    list.extend([i32::MIN, i32::MAX, 0, -1]);
+    radix_sort(&mut list);
+    assert!(is_sorted(&list, |num1: &i32, num2: &i32| num1 <= num2));
+
+    let mut list: Vec<i8> = (i8::MIN..=i8::MAX).rev().collect();
+    radix_sort(&mut list);
+    assert_eq!((i8::MIN..=i8::MAX).collect::<Vec<i8>>(), list);
+
+    let mut list: Vec<u64> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(|num| (num as u64) << 20)
+        .collect();
+    radix_sort(&mut list);
+    assert!(is_sorted(&list, |num1: &u64, num2: &u64| num1 <= num2));
+
+    let mut list: Vec<i128> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(|num| num as i128 * i64::MAX as i128)
+        .collect();
+    radix_sort(&mut list);
+    assert!(is_sorted(&list, |num1: &i128, num2: &i128| num1 <= num2));
+
+    // floats end up in the same order as `total_cmp`
+    let mut list: Vec<f64> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(|num| num as f64 / 1000.0)
+        .collect();
+    list.extend([
+        f64::NAN,
+        -f64::NAN,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        0.0,
+        -0.0,
+        f64::MIN_POSITIVE,
+    ]);
+    let mut expected = list.clone();
+    merge_sort_bottom_up_by(&mut expected, f64::total_cmp);
+    radix_sort(&mut list);
+    let to_bits = |list: &[f64]| list.iter().map(|num| num.to_bits()).collect::<Vec<u64>>();
+    assert_eq!(to_bits(&expected), to_bits(&list));
+
+    // sorting structs by a key is stable, and only gets each key once
+    let list: Vec<(i32, usize)> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, num)| (num % 20, idx))
+        .collect();
+    let mut sorted_list = list.clone();
+    let num_keys = Cell::new(0);
+    radix_sort_by_key(&mut sorted_list, |pair| {
+        num_keys.set(num_keys.get() + 1);
+        pair.0
+    });
+    assert_eq!(list.len(), num_keys.get());
+    let mut expected = list;
+    merge_sort_bottom_up_by(&mut expected, |pair1, pair2| pair1.0.cmp(&pair2.0));
+    assert_eq!(expected, sorted_list);
+
+    let mut list: Vec<String> = vec![];
+    radix_sort_by_key(&mut list, |string| string.len());
+    assert!(list.is_empty());
+    let mut list = vec![String::from("ccc"), String::from("a"), String::from("bb")];
+    radix_sort_by_key(&mut list, |string| string.len());
+    assert_eq!(vec!["a", "bb", "ccc"], list);
 }
 
 #[test]
@@ -233,31 +1111,683 @@ fn heap_sort_test() {
 
     let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
     list = heap_sort(list, &ascending_sort_closure);
-    assert_eq!(true, is_sorted(&list, &ascending_is_eq_closure));
+    assert!(is_sorted(&list, &ascending_is_eq_closure));
 
     let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
     list = heap_sort(list, &descending_sort_closure);
-    assert_eq!(true, is_sorted(&list, &descending_is_eq_closure));
+    assert!(is_sorted(&list, &descending_is_eq_closure));
+
+    // sort a borrowed slice in place
+    let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+    heap_sort_in_place(&mut list[..], &ascending_sort_closure);
+    assert!(is_sorted(&list, &ascending_is_eq_closure));
+}
+
+#[test]
+fn heap_sort_in_place_test() {
+    // a type that can't be cloned, which the in place heapsort doesn't need
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Handle(i32);
+
+    let mut list: Vec<Handle> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .map(Handle)
+        .collect();
+    heap_sort_by(&mut list, |handle1, handle2| handle1.cmp(handle2));
+    assert!(is_sorted(&list, |handle1: &Handle, handle2: &Handle| {
+        handle1 <= handle2
+    }));
+
+    // two elements and an even number of elements, where the last parent has one child
+    let mut list = vec![2, 1];
+    heap_sort_ord(&mut list);
+    assert_eq!(vec![1, 2], list);
+
+    let mut list = vec![6, 3, 5, 1, 4, 2];
+    heap_sort_ord(&mut list);
+    assert_eq!(vec![1, 2, 3, 4, 5, 6], list);
+}
+
+#[test]
+fn heap_priority_queue_test() {
+    let mut the_heap = Heap::new();
+    assert_eq!(None, the_heap.pop());
+
+    let list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+    for num in &list {
+        the_heap.push(*num);
+    }
+    assert_eq!(list.len(), the_heap.len());
+    assert_eq!(list.iter().min(), the_heap.peek());
+
+    // popping everything gives back the elements in ascending order
+    let mut popped = vec![];
+    while let Some(num) = the_heap.pop() {
+        popped.push(num);
+    }
+    assert!(the_heap.is_empty());
+    assert!(is_sorted(&popped, |num1: &i32, num2: &i32| num1 <= num2));
+
+    // heapify all at once, with a comparator that puts the largest element on top
+    let the_heap = Heap::from_iter_by(list.clone(), |num1: &i32, num2: &i32| num2.cmp(num1));
+    assert_eq!(list.iter().max(), the_heap.peek());
+    let sorted = the_heap.into_sorted_vec();
+    assert!(is_sorted(&sorted, |num1: &i32, num2: &i32| num1 >= num2));
+
+    let the_heap: Heap<i32> = list.iter().copied().collect();
+    assert_eq!(list.len(), the_heap.len());
+
+    // decreasing keys moves elements to the top, and handles stop working once popped
+    let mut the_heap = Heap::new();
+    let handles: Vec<_> = (0..100).map(|num| the_heap.push(num * 10)).collect();
+    assert_eq!(Ok(500), the_heap.decrease_key(handles[50], -1));
+    assert_eq!(Some(&-1), the_heap.peek());
+    assert_eq!(Ok(990), the_heap.decrease_key(handles[99], 5));
+    assert_eq!(Some(&5), the_heap.get(handles[99]));
+    assert_eq!(Some(-1), the_heap.pop());
+    assert_eq!(Some(0), the_heap.pop());
+    assert_eq!(Some(5), the_heap.pop());
+    assert!(!the_heap.contains(handles[50]));
+    assert_eq!(Err(3), the_heap.decrease_key(handles[50], 3));
+
+    // a new element reuses the slot, but not the old handle
+    let new_handle = the_heap.push(1);
+    assert!(the_heap.contains(new_handle));
+    assert!(!the_heap.contains(handles[99]));
+    assert_eq!(Some(1), the_heap.pop());
+    assert_eq!(Some(10), the_heap.pop());
+}
+
+#[test]
+fn sorter_test() {
+    let pool = SortPool::new(4).expect("Error when creating the sort pool");
+    let sorters: Vec<Box<dyn Sorter<i32> + '_>> = all_sorters(&pool);
+
+    for sorter in sorters {
+        // create a list of elements
+        let mut list = vec![4, 5, 2, 1, 3];
+        sorter.sort_by(&mut list, &|num1, num2| num1.cmp(num2));
+        assert_eq!(vec![1, 2, 3, 4, 5], list, "{} failed", sorter.name());
+
+        let mut list: Vec<i32> = vec![];
+        sorter.sort_by(&mut list, &|num1, num2| num1.cmp(num2));
+        let test: Vec<i32> = vec![];
+        assert_eq!(test, list, "{} failed", sorter.name());
+
+        // descending order sort
+        let mut list = vec![4, 5, 2, 1, 3];
+        sorter.sort_by(&mut list, &|num1, num2| num2.cmp(num1));
+        assert_eq!(vec![5, 4, 3, 2, 1], list, "{} failed", sorter.name());
+
+        // duplicates are handled the same way no matter which algorithm is behind the trait
+        let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+        list.extend_from_within(..);
+        sorter.sort_by(&mut list, &|num1, num2| num1.cmp(num2));
+        assert!(
+            is_sorted(&list, |num1: &i32, num2: &i32| -> bool { num1 <= num2 }),
+            "{} failed",
+            sorter.name()
+        );
+
+        // every sort can be called through `try_sort_by` too
+        let mut list = vec![4, 5, 2, 1, 3];
+        assert_eq!(
+            Ok(()),
+            sorter.try_sort_by(&mut list, &|num1, num2| num1.cmp(num2)),
+            "{} failed",
+            sorter.name()
+        );
+        assert_eq!(vec![1, 2, 3, 4, 5], list, "{} failed", sorter.name());
+    }
+
+    // the sorts that can fail hand their error back through the trait
+    let panicking_compare = |_: &i32, _: &i32| -> Ordering { panic!("comparator panicked") };
+    let failing_sorters: Vec<Box<dyn Sorter<i32> + '_>> = vec![
+        Box::new(MergeSortMultithread { num_threads: 4 }),
+        Box::new(MergeSortPooled { pool: &pool }),
+        Box::new(QuickSortParallel { num_threads: 4 }),
+        Box::new(QuickSortPooled { pool: &pool }),
+    ];
+    for sorter in failing_sorters {
+        let mut list = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN);
+        assert_eq!(
+            Err(SortError::WorkerPanicked(String::from(
+                "comparator panicked"
+            ))),
+            sorter.try_sort_by(&mut list, &panicking_compare),
+            "{} failed",
+            sorter.name()
+        );
+    }
+
+    let mut list = vec![4, 5, 2, 1, 3];
+    let sorter: Box<dyn Sorter<i32>> = Box::new(QuickSortParallel { num_threads: 0 });
+    assert_eq!(
+        Err(SortError::InvalidThreadCount),
+        sorter.try_sort_by(&mut list, &|num1, num2| num1.cmp(num2))
+    );
+    let sorter: Box<dyn Sorter<i32>> = Box::new(Checked { sorter: QuickSort });
+    assert!(matches!(
+        sorter.try_sort_by(&mut list, &|_, _| Ordering::Less),
+        Err(SortError::InconsistentComparator(_))
+    ));
 }
 
-/// Checks to see if a vector is sorted.
-pub fn is_sorted<U, T>(list: &Vec<T>, in_order: U) -> bool
-where
-    T: Debug,
-    U: Fn(&T, &T) -> bool,
-{
-    let mut output = true;
-    for i in 1..list.len() {
-        if !in_order(&list[i - 1], &list[i]) {
-            output = false;
-            println!(
-                "ERROR: Element {:?} should not come before element {:?}",
-                list[i - 1],
-                list[i]
+#[test]
+fn ordering_api_test() {
+    type SortBy = fn(&mut [i32], fn(&i32, &i32) -> Ordering);
+    type SortByKey = fn(&mut [i32], fn(&i32) -> i64);
+    type SortOrd = fn(&mut [i32]);
+    let sorts: Vec<(SortBy, SortByKey, SortOrd)> = vec![
+        (bubble_sort_by, bubble_sort_by_key, bubble_sort_ord),
+        (insertion_sort_by, insertion_sort_by_key, insertion_sort_ord),
+        (selection_sort_by, selection_sort_by_key, selection_sort_ord),
+        (
+            merge_sort_top_down_by,
+            merge_sort_top_down_by_key,
+            merge_sort_top_down_ord,
+        ),
+        (
+            merge_sort_bottom_up_by,
+            merge_sort_bottom_up_by_key,
+            merge_sort_bottom_up_ord,
+        ),
+        (tim_sort_by, tim_sort_by_key, tim_sort_ord),
+        (quick_sort_by, quick_sort_by_key, quick_sort_ord),
+        (
+            quick_sort_three_way_by,
+            quick_sort_three_way_by_key,
+            quick_sort_three_way_ord,
+        ),
+        (
+            dual_pivot_quick_sort_by,
+            dual_pivot_quick_sort_by_key,
+            dual_pivot_quick_sort_ord,
+        ),
+        (intro_sort_by, intro_sort_by_key, intro_sort_ord),
+        (pdq_sort_by, pdq_sort_by_key, pdq_sort_ord),
+        (heap_sort_by, heap_sort_by_key, heap_sort_ord),
+        (stable_sort_by, stable_sort_by_key, stable_sort_ord),
+    ];
+
+    for (sort_by, sort_by_key, sort_ord) in sorts {
+        let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+        sort_by(&mut list, |num1, num2| num2.cmp(num1));
+        assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+            num1 >= num2
+        }));
+
+        // sort by the negated value, which is descending order again
+        let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+        sort_by_key(&mut list, |num| -(*num as i64));
+        assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+            num1 >= num2
+        }));
+
+        let mut list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+        sort_ord(&mut list);
+        assert!(is_sorted(&list, |num1: &i32, num2: &i32| -> bool {
+            num1 <= num2
+        }));
+    }
+}
+
+#[test]
+fn in_order_strictness_test() {
+    // lots of duplicates, which used to break quicksort and heapsort with `<=`
+    let list: Vec<i32> = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN)
+        .iter()
+        .map(|num| num % 10)
+        .collect();
+    let strict_closure = |num1: &i32, num2: &i32| -> bool { num1 < num2 };
+    let non_strict_closure = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+
+    for in_order in [strict_closure, non_strict_closure] {
+        assert!(is_sorted(
+            &quick_sort(list.clone(), &in_order),
+            non_strict_closure
+        ));
+        assert!(is_sorted(
+            &heap_sort(list.clone(), &in_order),
+            non_strict_closure
+        ));
+        assert!(is_sorted(
+            &merge_sort_top_down(&list, &in_order),
+            non_strict_closure
+        ));
+    }
+}
+
+#[test]
+fn comparator_panic_safety_test() {
+    // keeps track of how many copies of each element are alive, so that a leak or a double
+    // drop shows up as a count other than one
+    #[derive(Debug)]
+    struct Tracked<'a> {
+        id: usize,
+        value: i32,
+        live_counts: &'a [AtomicIsize],
+    }
+
+    impl Clone for Tracked<'_> {
+        fn clone(&self) -> Self {
+            self.live_counts[self.id].fetch_add(1, AtomicOrdering::SeqCst);
+            Tracked { ..*self }
+        }
+    }
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.live_counts[self.id].fetch_sub(1, AtomicOrdering::SeqCst);
+        }
+    }
+
+    let values = generate_rand_vec(ADVANCED_SORT_RAND_VEC_LEN / 4);
+    let live_counts: Vec<AtomicIsize> = values.iter().map(|_| AtomicIsize::new(0)).collect();
+    let new_list = || -> Vec<Tracked> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(id, &value)| {
+                live_counts[id].fetch_add(1, AtomicOrdering::SeqCst);
+                Tracked {
+                    id,
+                    value,
+                    live_counts: &live_counts,
+                }
+            })
+            .collect()
+    };
+    let all_live_counts_are = |expected: isize| {
+        live_counts
+            .iter()
+            .all(|count| count.load(AtomicOrdering::SeqCst) == expected)
+    };
+
+    let pool = SortPool::new(4).expect("Error when creating the sort pool");
+    let sorters: Vec<Box<dyn Sorter<Tracked> + '_>> = all_sorters(&pool);
+
+    // panic right away, partway through, and (for the faster sorts) close to the end
+    for panic_after in [0, 100, 20000, 100000] {
+        for sorter in &sorters {
+            let num_comparisons = AtomicUsize::new(0);
+            let compare = |tracked1: &Tracked, tracked2: &Tracked| {
+                if num_comparisons.fetch_add(1, AtomicOrdering::SeqCst) >= panic_after {
+                    panic!("comparator panicked");
+                }
+                tracked1.value.cmp(&tracked2.value)
+            };
+
+            let mut list = new_list();
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| sorter.sort_by(&mut list, &compare)));
+            if result.is_ok() {
+                // the sort finished before the comparator got the chance to panic
+                continue;
+            }
+
+            // every element is still in the list exactly once
+            let mut ids: Vec<usize> = list.iter().map(|tracked| tracked.id).collect();
+            ids.sort_unstable();
+            assert!(
+                ids.iter().copied().eq(0..values.len()),
+                "{} failed",
+                sorter.name()
+            );
+            assert!(all_live_counts_are(1), "{} failed", sorter.name());
+
+            drop(list);
+            assert!(all_live_counts_are(0), "{} failed", sorter.name());
+        }
+    }
+
+    // the sorts that take the `Vec` drop every element exactly once
+    let panicking_in_order = |_: &Tracked, _: &Tracked| -> bool { panic!("comparator panicked") };
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| quick_sort(
+        new_list(),
+        &panicking_in_order
+    )))
+    .is_err());
+    assert!(all_live_counts_are(0));
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| heap_sort(
+        new_list(),
+        &panicking_in_order
+    )))
+    .is_err());
+    assert!(all_live_counts_are(0));
+
+    // while the ones that borrow a slice keep every element in it
+    type InPlaceSort = fn(&mut [Tracked], &dyn Fn(&Tracked, &Tracked) -> bool);
+    let in_place_sorts: [(&str, InPlaceSort); 2] = [
+        ("quick_sort_in_place", |list, in_order| {
+            quick_sort_in_place(list, in_order)
+        }),
+        ("heap_sort_in_place", |list, in_order| {
+            heap_sort_in_place(list, in_order)
+        }),
+    ];
+    for panic_after in [0, 100, 20000] {
+        for (name, sort) in in_place_sorts {
+            let num_comparisons = AtomicUsize::new(0);
+            let in_order = |tracked1: &Tracked, tracked2: &Tracked| -> bool {
+                if num_comparisons.fetch_add(1, AtomicOrdering::SeqCst) >= panic_after {
+                    panic!("comparator panicked");
+                }
+                tracked1.value < tracked2.value
+            };
+
+            let mut list = new_list();
+            assert!(
+                panic::catch_unwind(AssertUnwindSafe(|| sort(&mut list, &in_order))).is_err(),
+                "{} failed",
+                name
             );
-            break;
+
+            let mut ids: Vec<usize> = list.iter().map(|tracked| tracked.id).collect();
+            ids.sort_unstable();
+            assert!(ids.iter().copied().eq(0..values.len()), "{} failed", name);
+            assert!(all_live_counts_are(1), "{} failed", name);
+
+            drop(list);
+            assert!(all_live_counts_are(0), "{} failed", name);
         }
     }
+}
+
+#[test]
+fn validate_comparator_test() {
+    let sample = generate_rand_vec(30);
+
+    // consistent comparators, including an `in_order` closure with `<=`
+    assert_eq!(Ok(()), validate_comparator(&sample, i32::cmp));
+    assert_eq!(
+        Ok(()),
+        validate_comparator(&sample, |num1, num2| num2.cmp(num1))
+    );
+    assert_eq!(
+        Ok(()),
+        validate_comparator(&sample, |num1, num2| (num1 % 10).cmp(&(num2 % 10)))
+    );
+    let non_strict = |num1: &i32, num2: &i32| -> bool { num1 <= num2 };
+    assert_eq!(
+        Ok(()),
+        validate_comparator(&sample, |num1, num2| {
+            ordering_from_in_order(&non_strict, num1, num2)
+        })
+    );
+
+    // turning `<=` straight into an ordering makes elements come before themselves
+    let sample = vec![1, 2, 3];
+    let result = validate_comparator(&sample, |num1, num2| {
+        if num1 <= num2 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+    assert_eq!(
+        Err(ComparatorViolation::Irreflexivity { element: &1 }),
+        result
+    );
+
+    let result = validate_comparator(&sample, |num1, num2| {
+        if num1 == num2 {
+            Ordering::Equal
+        } else {
+            Ordering::Less
+        }
+    });
+    assert_eq!(
+        Err(ComparatorViolation::Asymmetry {
+            first: &1,
+            second: &2
+        }),
+        result
+    );
+
+    // rock, paper, scissors
+    let sample = vec![0, 1, 2];
+    let result = validate_comparator(&sample, |num1, num2| {
+        if num1 == num2 {
+            Ordering::Equal
+        } else if (num1 + 1) % 3 == *num2 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+    assert_eq!(
+        Err(ComparatorViolation::Transitivity {
+            first: &0,
+            second: &1,
+            third: &2
+        }),
+        result
+    );
+    assert_eq!(
+        "0, 1 and 2 are in order pairwise, but 0 and 2 are not",
+        result.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn checked_sorter_test() {
+    let sorters: Vec<Box<dyn Sorter<i32>>> = vec![
+        Box::new(Checked {
+            sorter: InsertionSort,
+        }),
+        Box::new(Checked {
+            sorter: MergeSortBottomUp,
+        }),
+        Box::new(Checked {
+            sorter: MergeSortMultithread { num_threads: 4 },
+        }),
+        Box::new(Checked { sorter: QuickSort }),
+        Box::new(Checked { sorter: HeapSort }),
+    ];
+
+    let list = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN);
+    for sorter in sorters {
+        let mut sorted_list = list.clone();
+        sorter.sort_by(&mut sorted_list, &|num1, num2| num1.cmp(num2));
+        assert!(
+            is_sorted(&sorted_list, |num1: &i32, num2: &i32| num1 <= num2),
+            "{} failed",
+            sorter.name()
+        );
+    }
+
+    // caught by the sample before sorting
+    let checked = Checked { sorter: QuickSort };
+    let mut sorted_list = list.clone();
+    assert_eq!(
+        Err(SortError::InconsistentComparator(format!(
+            "{} is not equal to itself",
+            list[0]
+        ))),
+        checked.try_sort_by(&mut sorted_list, &|_, _| Ordering::Less)
+    );
+
+    // caught while sorting, as 1 and 2 are too close to the end to be in the sample
+    let mut sorted_list: Vec<i32> = (0..BASIC_SORT_RAND_VEC_LEN as i32).rev().collect();
+    let result = checked.try_sort_by(&mut sorted_list, &|num1, num2| {
+        if num1 != num2 && [1, 2].contains(num1) && [1, 2].contains(num2) {
+            Ordering::Less
+        } else {
+            num1.cmp(num2)
+        }
+    });
+    // the sort could have compared them either way around
+    let expected_messages = [(1, 2), (2, 1)].map(|(first, second)| {
+        format!(
+            "comparing {} with {} doesn't give the reverse of comparing them the other way",
+            first, second
+        )
+    });
+    match result {
+        Err(SortError::InconsistentComparator(message)) => {
+            assert!(expected_messages.contains(&message), "{}", message)
+        }
+        other => panic!("expected an inconsistent comparator, got {:?}", other),
+    }
+
+    let panicking_result = panic::catch_unwind(|| {
+        let mut sorted_list = list.clone();
+        Checked { sorter: HeapSort }.sort_by(&mut sorted_list, &|num1, num2| {
+            if num1 <= num2 {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        });
+    });
+    assert!(panicking_result.is_err());
+}
+
+#[test]
+fn inconsistent_comparator_test() {
+    type SortComparator<'a> = &'a (dyn Fn(&i32, &i32) -> Ordering + Sync);
+
+    // a cheap xorshift, so that every thread gets its own stream of random answers
+    fn random_ordering(state: &AtomicUsize) -> Ordering {
+        let mut random = state.load(AtomicOrdering::Relaxed);
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+        state.store(random, AtomicOrdering::Relaxed);
+        match random % 3 {
+            0 => Ordering::Less,
+            1 => Ordering::Equal,
+            _ => Ordering::Greater,
+        }
+    }
+
+    let pool = SortPool::new(4).expect("Error when creating the sort pool");
+    let sorters: Vec<Box<dyn Sorter<i32> + '_>> = all_sorters(&pool);
+
+    // just long enough for the parallel quicksorts to split up the work
+    let list = generate_rand_vec(5000);
+    let mut expected = list.clone();
+    expected.sort_unstable();
+
+    for sorter in &sorters {
+        let state = AtomicUsize::new(0x2545_f491);
+        let comparators: [SortComparator; 3] = [
+            &|_, _| Ordering::Less,
+            &|_, _| Ordering::Greater,
+            &|_, _| random_ordering(&state),
+        ];
+
+        for compare in comparators {
+            // the order is meaningless, but every element has to still be there
+            let mut sorted_list = list.clone();
+            sorter.sort_by(&mut sorted_list, compare);
+            sorted_list.sort_unstable();
+            assert_eq!(expected, sorted_list, "{} failed", sorter.name());
+        }
+    }
+
+    // the rest of the public entry points that take a comparator
+    let state = AtomicUsize::new(0x2545_f491);
+    let shards = vec![list.clone(), list.clone(), list.clone()];
+    let merged = merge_k_sorted(shards.clone(), |_: &i32, _: &i32| random_ordering(&state));
+    assert_eq!(list.len() * 3, merged.len());
+    let merged: Vec<i32> = MergeIter::new(shards, |_: &i32, _: &i32| random_ordering(&state))
+        .dedup()
+        .collect();
+    assert!(merged.len() <= list.len() * 3);
+
+    let mut the_heap = Heap::with_comparator(|_: &i32, _: &i32| random_ordering(&state));
+    for &num in &list {
+        the_heap.push(num);
+    }
+    let mut popped = vec![];
+    while let Some(num) = the_heap.pop() {
+        popped.push(num);
+    }
+    popped.sort_unstable();
+    assert_eq!(expected, popped);
+
+    let sorted_list = merge_sort_top_down(&list, &|_: &i32, _: &i32| -> bool {
+        random_ordering(&state) == Ordering::Less
+    });
+    assert_eq!(list.len(), sorted_list.len());
+}
+
+#[test]
+fn stability_test() {
+    // only the first value is compared, the second one is the original index
+    let list: Vec<(i32, usize)> = generate_rand_vec(BASIC_SORT_RAND_VEC_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, num)| (num % 20, idx))
+        .collect();
+    let is_stably_sorted = |sorted_list: &[(i32, usize)]| {
+        sorted_list.len() == list.len()
+            && is_sorted(sorted_list, |pair1: &(i32, usize), pair2| {
+                pair1.0 < pair2.0 || (pair1.0 == pair2.0 && pair1.1 < pair2.1)
+            })
+    };
+
+    // every sorter that claims to be stable has to keep equal keys in their original order
+    let pool = SortPool::new(4).expect("Error when creating the sort pool");
+    let mut sorters: Vec<Box<dyn Sorter<(i32, usize)> + '_>> = all_sorters(&pool);
+    // an odd number of chunks leaves a run out of every other round of merging
+    sorters.push(Box::new(MergeSortMultithread { num_threads: 3 }));
+    sorters.push(Box::new(Checked {
+        sorter: MergeSortBottomUp,
+    }));
+    for sorter in sorters {
+        let mut sorted_list = list.clone();
+        sorter.sort_by(&mut sorted_list, &|pair1, pair2| pair1.0.cmp(&pair2.0));
+        if sorter.capabilities().stable {
+            assert!(is_stably_sorted(&sorted_list), "{} failed", sorter.name());
+        }
+    }
+
+    // the `in_order` versions of the stable sorts are stable with both `<` and `<=`
+    let strict_closure = |pair1: &(i32, usize), pair2: &(i32, usize)| -> bool { pair1.0 < pair2.0 };
+    let non_strict_closure =
+        |pair1: &(i32, usize), pair2: &(i32, usize)| -> bool { pair1.0 <= pair2.0 };
+    for in_order in [strict_closure, non_strict_closure] {
+        let mut sorted_list = list.clone();
+        bubble_sort(&mut sorted_list, in_order);
+        assert!(is_stably_sorted(&sorted_list));
+
+        let mut sorted_list = list.clone();
+        insertion_sort(&mut sorted_list, in_order);
+        assert!(is_stably_sorted(&sorted_list));
+
+        assert!(is_stably_sorted(&merge_sort_top_down(&list, &in_order)));
+
+        let mut sorted_list = list.clone();
+        merge_sort_bottom_up(&mut sorted_list, in_order);
+        assert!(is_stably_sorted(&sorted_list));
+
+        let mut sorted_list = list.clone();
+        tim_sort(&mut sorted_list, in_order);
+        assert!(is_stably_sorted(&sorted_list));
+
+        let mut sorted_list = list.clone();
+        merge_sort_top_down_multithread(&mut sorted_list, &in_order, 5)
+            .expect("Error when doing multithreaded merge sort");
+        assert!(is_stably_sorted(&sorted_list));
+
+        let mut sorted_list = list.clone();
+        stable_sort(&mut sorted_list, in_order);
+        assert!(is_stably_sorted(&sorted_list));
+    }
+
+    let mut sorted_list = list.clone();
+    stable_sort_by(&mut sorted_list, |pair1, pair2| pair1.0.cmp(&pair2.0));
+    assert!(is_stably_sorted(&sorted_list));
+
+    let mut sorted_list = list.clone();
+    stable_sort_by_key(&mut sorted_list, |pair| pair.0);
+    assert!(is_stably_sorted(&sorted_list));
 
-    output
+    // the unstable sorts really can reorder equal keys, so the flag isn't just conservative
+    let mut sorted_list = list.clone();
+    heap_sort_by(&mut sorted_list, |pair1, pair2| pair1.0.cmp(&pair2.0));
+    assert!(!is_stably_sorted(&sorted_list));
 }